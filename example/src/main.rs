@@ -136,7 +136,6 @@ fn snapshot_example() {
 
     let start = Instant::now();
     let mut last_snapshot_send = Instant::now();
-    let mut last_step_time = Instant::now();
 
     let mut pipeline_snapshots = Vec::new();
 
@@ -177,11 +176,7 @@ fn snapshot_example() {
         red_rect.x += dx as i32;
         red_rect.y += dy as i32;
 
-        let now = Instant::now();
-        let delta_time = now.duration_since(last_step_time);
-        last_step_time = now;
-
-        if let Some(pos) = play.step(delta_time.as_secs_f64(), &buf) {
+        if let Some(pos) = play.step_instant(Instant::now(), &buf) {
             green_rect.x = pos.x.round() as i32;
             green_rect.y = pos.y.round() as i32;
         }
@@ -247,34 +242,6 @@ fn snapshot_example() {
             ::std::thread::sleep(Duration::from_millis(2));
         }
 
-        println!(
-            "dbg_extrapolating {} (GOOD 0 - 10 BAD)",
-            (play.db_extrapolating_ema.value.unwrap_or_default() * 10.0).round()
-        );
-        println!(
-            "dbg_clamping      {} (GOOD 0 - 10 BAD)",
-            (play.db_clamping_ema.value.unwrap_or_default() * 10.0).round()
-        );
-        println!(
-            "dbg_scaling       {} (GOOD 0 - 10 BAD)",
-            (play.db_scaling_ema.value.unwrap_or_default() * 10.0).round()
-        );
-        println!(
-            "catchup time  ({} <=) {}ms (<= {}) - targets 0 ms (time scaling: {} - {})",
-            -(SETTINGS.playback_clamp() * 1000.0),
-            (play.catchup_time.value.unwrap_or_default() * 1000.0).round(),
-            SETTINGS.playback_clamp() * 1000.0,
-            SETTINGS.slow_threshold() * 1000.0,
-            SETTINGS.fast_threshold() * 1000.0,
-        );
-        println!(
-            "dyn playback time {}ms",
-            (buf.dynamic_playback_offset() * 1000.0).round()
-        );
-        println!(
-            "remote delta time {}ms - targets time period + latency (+ frame time)",
-            (buf.remote_delta_time.value.unwrap_or_default() * 1000.0).round()
-        );
-        println!("timescale     {}", play.timescale);
+        print!("{}", play.debug_report(&buf));
     }
 }
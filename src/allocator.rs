@@ -0,0 +1,71 @@
+//! The allocator a `Buffer` stores its snapshots with, see
+//! `Buffer::new_in`. With the `allocator_api` feature (nightly only) any
+//! `std::alloc::Allocator`, otherwise only the global allocator.
+
+#[cfg(feature = "allocator_api")]
+pub use std::alloc::{Allocator, Global};
+
+#[cfg(not(feature = "allocator_api"))]
+pub use stable::{Allocator, Global};
+
+/// The snapshot storage of a `Buffer`, using its allocator
+#[cfg(feature = "allocator_api")]
+pub(crate) type Deque<T, A> = std::collections::VecDeque<T, A>;
+
+#[cfg(not(feature = "allocator_api"))]
+pub(crate) use stable::Deque;
+
+#[cfg(not(feature = "allocator_api"))]
+mod stable {
+    use std::{
+        collections::VecDeque,
+        marker::PhantomData,
+        ops::{Deref, DerefMut},
+    };
+
+    mod sealed {
+        pub trait Sealed {}
+    }
+
+    /// Stands in for `std::alloc::Allocator` without the `allocator_api`
+    /// feature, implemented only by `Global`
+    pub trait Allocator: sealed::Sealed {}
+
+    /// Stands in for `std::alloc::Global` without the `allocator_api`
+    /// feature, the global allocator
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct Global;
+
+    impl sealed::Sealed for Global {}
+    impl Allocator for Global {}
+
+    /// A `VecDeque` tagged with its (always global) allocator, mirroring
+    /// `VecDeque<T, A>` on stable
+    pub(crate) struct Deque<T, A>(VecDeque<T>, PhantomData<A>);
+
+    impl<T, A: Allocator> Deque<T, A> {
+        pub(crate) fn with_capacity_in(capacity: usize, _alloc: A) -> Self {
+            Self(VecDeque::with_capacity(capacity), PhantomData)
+        }
+    }
+
+    impl<T, A> Deref for Deque<T, A> {
+        type Target = VecDeque<T>;
+
+        fn deref(&self) -> &VecDeque<T> {
+            &self.0
+        }
+    }
+
+    impl<T, A> DerefMut for Deque<T, A> {
+        fn deref_mut(&mut self) -> &mut VecDeque<T> {
+            &mut self.0
+        }
+    }
+
+    impl<T: Clone, A> Clone for Deque<T, A> {
+        fn clone(&self) -> Self {
+            Self(self.0.clone(), PhantomData)
+        }
+    }
+}
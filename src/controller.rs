@@ -0,0 +1,11 @@
+/// Computes the playback timescale from the catchup error, in place of
+/// the built-in slow/normal/fast speeds. See
+/// `Playback::with_timescale_controller`.
+pub trait TimescaleController {
+    /// `error` is the smoothed catchup time, the target playback time less
+    /// the actual playback time, positive when behind (seconds). `dt` is
+    /// the local time passed since the last call (seconds).
+    ///
+    /// Returns the rate at which the playback time should pass.
+    fn compute(&mut self, error: f64, dt: f64) -> f64;
+}
@@ -0,0 +1,67 @@
+use crate::linear_map;
+
+/// A designer tunable easing curve, a table of `t -> t'` applied to the
+/// interpolation parameter, see `Playback::with_easing_curve`.
+#[derive(Clone, Debug)]
+pub struct EasingCurve {
+    /// Sorted by `t`
+    points: Vec<(f64, f64)>,
+}
+
+impl EasingCurve {
+    /// Construct from a table of `(t, t')` points, in any order
+    pub fn new(mut points: Vec<(f64, f64)>) -> Self {
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        EasingCurve { points }
+    }
+
+    /// Linearly interpolate the table at `t`, holding the first and last
+    /// points beyond either end. An empty table leaves `t` unchanged.
+    pub fn sample(&self, t: f64) -> f64 {
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            return t;
+        };
+
+        if t <= first.0 {
+            return first.1;
+        }
+        if t >= last.0 {
+            return last.1;
+        }
+
+        let upper = self.points.partition_point(|point| point.0 <= t);
+        let (t0, eased0) = self.points[upper - 1];
+        let (t1, eased1) = self.points[upper];
+        linear_map(t, t0, t1, eased0, eased1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::EasingCurve;
+
+    #[test]
+    fn easing_test() {
+        let linear = EasingCurve::new(vec![(0.0, 0.0), (1.0, 1.0)]);
+        for i in 0..=20 {
+            let t = i as f64 / 20.0;
+            assert!((linear.sample(t) - t).abs() < 1e-9);
+        }
+
+        // Ease in, given out of order
+        let ease_in = EasingCurve::new(vec![(1.0, 1.0), (0.0, 0.0), (0.5, 0.2), (0.75, 0.5)]);
+        assert!((ease_in.sample(0.25) - 0.1).abs() < 1e-9);
+        assert!((ease_in.sample(0.5) - 0.2).abs() < 1e-9);
+
+        let mut last = ease_in.sample(0.0);
+        for i in 1..=20 {
+            let eased = ease_in.sample(i as f64 / 20.0);
+            assert!(eased >= last);
+            last = eased;
+        }
+        assert_eq!(ease_in.sample(-1.0), 0.0);
+        assert_eq!(ease_in.sample(2.0), 1.0);
+
+        assert_eq!(EasingCurve::new(Vec::new()).sample(0.3), 0.3);
+    }
+}
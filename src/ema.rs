@@ -1,6 +1,9 @@
-#[derive(Clone)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExponentialMovingAverage {
     alpha: f64,
+    /// The decayed count of samples added, see `effective_samples`
+    weight: f64,
     pub var: f64,
     pub std_dev: f64,
     pub value: Option<f64>,
@@ -12,13 +15,34 @@ impl ExponentialMovingAverage {
     pub fn new(n: f64) -> ExponentialMovingAverage {
         ExponentialMovingAverage {
             alpha: 2.0 / (n + 1.0),
+            weight: 0.0,
             var: 0.0,
             std_dev: 0.0,
             value: None,
         }
     }
 
+    /// The window `n` of historic values relevant to the moving average
+    pub fn window(&self) -> f64 {
+        2.0 / self.alpha - 1.0
+    }
+
+    /// Resize the window of historic values relevant to the moving
+    /// average, keeping the current value and variance.
+    pub fn set_window(&mut self, n: f64) {
+        self.alpha = 2.0 / (n + 1.0);
+    }
+
+    /// How many samples the average effectively holds, growing from zero
+    /// with each `add` toward `1 / alpha` (roughly half the window). Useful
+    /// for deciding whether `std_dev` can be trusted yet.
+    pub fn effective_samples(&self) -> f64 {
+        self.weight
+    }
+
     pub fn add(&mut self, v: f64) {
+        self.weight = (1.0 - self.alpha) * self.weight + 1.0;
+
         if let Some(value) = self.value {
             let delta = v - value;
             self.value = Some(value + self.alpha * delta);
@@ -31,6 +55,7 @@ impl ExponentialMovingAverage {
 
     pub fn reset(&mut self) {
         self.value = None;
+        self.weight = 0.0;
         self.var = 0.0;
         self.std_dev = 0.0;
     }
@@ -69,4 +94,25 @@ mod tests {
 
         assert_eq!((ema.std_dev * 10000.0).round(), 2082470.0);
     }
+
+    #[test]
+    fn test_effective_samples() {
+        let mut ema = ExponentialMovingAverage::new(9.0);
+        assert_eq!(ema.effective_samples(), 0.0);
+
+        ema.add(1.0);
+        assert_eq!(ema.effective_samples(), 1.0);
+
+        // Grows with each sample toward 1 / alpha
+        let mut last = ema.effective_samples();
+        for _ in 0..100 {
+            ema.add(1.0);
+            assert!(ema.effective_samples() > last);
+            last = ema.effective_samples();
+        }
+        assert!((last - 5.0).abs() < 1e-6);
+
+        ema.reset();
+        assert_eq!(ema.effective_samples(), 0.0);
+    }
 }
@@ -0,0 +1,97 @@
+use std::f64::consts::TAU;
+
+/// The one-euro filter, an adaptive low-pass filter which smooths noise
+/// while slow, and reduces lag while fast. See `Playback::with_one_euro_filter`.
+///
+/// Casiez, Roussel and Vogel, "1€ Filter: A Simple Speed-based Low-pass
+/// Filter for Noisy Input in Interactive Systems", CHI 2012.
+#[derive(Clone, Debug)]
+pub struct OneEuroFilter {
+    /// The cutoff frequency while still, lower for less jitter (Hz)
+    pub min_cutoff: f64,
+
+    /// How much the cutoff rises with speed, higher for less lag
+    pub beta: f64,
+
+    /// The cutoff frequency for smoothing the speed (Hz)
+    pub d_cutoff: f64,
+
+    value: Option<f64>,
+    derivative: f64,
+}
+
+impl OneEuroFilter {
+    pub fn new(min_cutoff: f64, beta: f64) -> Self {
+        Self {
+            min_cutoff,
+            beta,
+            d_cutoff: 1.0,
+
+            value: None,
+            derivative: 0.0,
+        }
+    }
+
+    /// Filter the next value, passing in how much time has passed since
+    /// the last (seconds). Returns the filtered value.
+    pub fn filter(&mut self, value: f64, delta_time: f64) -> f64 {
+        let Some(previous) = self.value else {
+            self.value = Some(value);
+            return value;
+        };
+        if delta_time <= 0.0 {
+            return previous;
+        }
+
+        let derivative = (value - previous) / delta_time;
+        self.derivative += Self::alpha(self.d_cutoff, delta_time) * (derivative - self.derivative);
+
+        let cutoff = self.min_cutoff + self.beta * self.derivative.abs();
+        let filtered = previous + Self::alpha(cutoff, delta_time) * (value - previous);
+        self.value = Some(filtered);
+        filtered
+    }
+
+    pub fn reset(&mut self) {
+        self.value = None;
+        self.derivative = 0.0;
+    }
+
+    /// The smoothing factor of a low-pass filter at the cutoff frequency
+    fn alpha(cutoff: f64, delta_time: f64) -> f64 {
+        let tau = 1.0 / (TAU * cutoff);
+        1.0 / (1.0 + tau / delta_time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::OneEuroFilter;
+
+    #[test]
+    fn one_euro_test() {
+        let delta_time = 1.0 / 60.0;
+
+        // A noisy constant signal is smoothed
+        let mut filter = OneEuroFilter::new(1.0, 0.01);
+        let noise = |i: usize| if i.is_multiple_of(2) { 0.1 } else { -0.1 };
+        let mut max_deviation: f64 = 0.0;
+        for i in 0..300 {
+            let filtered = filter.filter(5.0 + noise(i), delta_time);
+            if i > 60 {
+                max_deviation = max_deviation.max((filtered - 5.0).abs());
+            }
+        }
+        assert!(max_deviation < 0.02, "{max_deviation}");
+
+        // A fast ramp isn't excessively lagged, where a plain 1Hz low-pass
+        // filter would lag by ~1.6
+        let mut filter = OneEuroFilter::new(1.0, 0.5);
+        let mut lag = 0.0;
+        for i in 0..120 {
+            let value = i as f64 * delta_time * 10.0;
+            lag = value - filter.filter(value, delta_time);
+        }
+        assert!(lag > 0.0 && lag < 0.2, "{lag}");
+    }
+}
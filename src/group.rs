@@ -0,0 +1,72 @@
+use crate::{Playback, Snapshot, SnapshotStore};
+
+/// Plays back two to four streams of snapshots with independent timing
+/// (e.g. positions and animation state sent at different rates) along one
+/// shared clock, so that each composite frame is temporally consistent.
+///
+/// `playbacks` is a tuple of the `Playback` of each stream, the first
+/// leading. The lead tracks its remote as usual, the others are stepped
+/// to the lead's remote time with `Playback::step_to`. Lead with the
+/// stream sent least often, so that the others have data buffered ahead
+/// of the shared time.
+pub struct PlaybackGroup<P> {
+    pub playbacks: P,
+}
+
+impl<P> PlaybackGroup<P> {
+    pub fn new(playbacks: P) -> Self {
+        Self { playbacks }
+    }
+}
+
+macro_rules! impl_playback_group {
+    ($($follower:ident $index:tt),+) => {
+        impl<A: Snapshot, $($follower: Snapshot),+> PlaybackGroup<(Playback<A>, $(Playback<$follower>,)+)> {
+            /// Step the shared clock by how much time has passed since the
+            /// last step (seconds), interpolating every stream, each over
+            /// its respective store, at the same remote time.
+            pub fn step(
+                &mut self,
+                delta_time: f64,
+                bufs: (&impl SnapshotStore<A>, $(&impl SnapshotStore<$follower>,)+),
+            ) -> (Option<A>, $(Option<$follower>,)+) {
+                let lead = self.playbacks.0.step(delta_time, bufs.0);
+                let remote_time = self.remote_time();
+                (lead, $(self.playbacks.$index.step_to(remote_time, bufs.$index),)+)
+            }
+
+            /// The shared remote time every stream was last interpolated at
+            /// (seconds, as given by `Snapshot::remote_time`)
+            pub fn remote_time(&self) -> f64 {
+                self.playbacks.0.time_epoch() + self.playbacks.0.playback_time
+            }
+        }
+    };
+}
+
+impl_playback_group!(B 1);
+impl_playback_group!(B 1, C 2);
+impl_playback_group!(B 1, C 2, D 3);
+
+/// Morph between the states of two playbacks (e.g. one entity possessing
+/// another), sampling each at its current playback time without stepping,
+/// and interpolating from `a` by `alpha` between 0.0 - `a`, and 1.0 - `b`.
+/// Step both playbacks as usual beforehand, and ramp `alpha` over the
+/// morph. `None` should either have nothing to play back.
+pub fn blend_playbacks<T: Snapshot>(
+    a: &Playback<T>,
+    b: &Playback<T>,
+    buf_a: &impl SnapshotStore<T>,
+    buf_b: &impl SnapshotStore<T>,
+    alpha: f64,
+) -> Option<T> {
+    let from = a.sample_offset(0.0, buf_a)?;
+    let to = b.sample_offset(0.0, buf_b)?;
+
+    // Exactly either stream at the ends
+    Some(match alpha {
+        ..=0.0 => from,
+        1.0.. => to,
+        _ => T::interpolate(alpha, &from, &to),
+    })
+}
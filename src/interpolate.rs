@@ -1,8 +1,14 @@
 // Based on Mirror for Unity's snapshot interpolation
 
-use std::{collections::VecDeque, marker::PhantomData, time::Instant};
+use std::{fmt::Write, marker::PhantomData, sync::Arc, time::Instant};
 
-use crate::{ExponentialMovingAverage, Settings, Snapshot, linear_map};
+use crate::{
+    Allocator, BufferState, CorrectionDirection, EasingCurve, ExponentialMovingAverage,
+    FrameSnapshot, OneEuroFilter, PlaybackState, Settings, Snapshot, SnapshotStore, TimeCollision,
+    TimescaleController,
+    allocator::{Deque, Global},
+    linear_map,
+};
 
 /// Buffers snapshots as they come in from the network so that
 /// they may be played back by a 'Playback' in live time, some
@@ -12,10 +18,17 @@ use crate::{ExponentialMovingAverage, Settings, Snapshot, linear_map};
 /// `Buffer` and `Playback` are split in order to allow a caller
 /// to insert snapshots and step the interpolator from two different
 /// threads without a lock.
-pub struct Buffer<T> {
+///
+/// Snapshots are stored with the allocator `A`, see `new_in`.
+///
+/// Snapshots are kept newest first, in descending `Snapshot::remote_time`,
+/// however they arrive. Positions (as with `get`, `iter` and
+/// `sample_at_detailed`) count from the newest, see `iter_chronological`
+/// for oldest first.
+pub struct Buffer<T, A: Allocator = Global> {
     settings: &'static Settings,
 
-    pub(crate) buf: VecDeque<T>,
+    pub(crate) buf: Deque<Buffered<T>, A>,
     buf_len: usize,
 
     last_remote_time: f64,
@@ -27,8 +40,231 @@ pub struct Buffer<T> {
     ///
     /// A moving average of the time between the latest two packets
     pub remote_delta_time: ExponentialMovingAverage,
+
+    /// Samples added to `remote_delta_time` since its window was last sized
+    remote_delta_samples: usize,
+
+    /// Consecutive samples excluded from `remote_delta_time` as outliers
+    remote_delta_spikes: usize,
+
+    /// The latest raw times between packets, newest first, see
+    /// `recent_deltas`
+    recent_deltas: RecentDeltas,
+
+    /// The latched dynamic playback offset, and the jitter measured when
+    /// it was latched, see `freeze_offset` (seconds)
+    frozen_offset: Option<(f64, f64)>,
+
+    /// See `SnapshotStore::time_epoch`
+    time_epoch: f64,
+
+    /// How many inserted snapshots were older than the newest snapshot
+    /// already buffered, yet still kept. Frequent violations suggest the
+    /// remote's clock isn't monotonic.
+    pub monotonic_violations: usize,
+
+    /// Rejects snapshots before insertion, see `set_validator`
+    validator: Option<Validator<T>>,
+
+    /// How many snapshots the validator has rejected
+    pub rejected_snapshots: usize,
+
+    /// Whether any snapshot was inserted with a time offset, otherwise
+    /// remote times needn't be looked up
+    time_offsets: bool,
+}
+
+/// Whether a snapshot is valid to insert, see `Buffer::set_validator`
+pub type Validator<T> = Arc<dyn Fn(&T) -> bool + Send + Sync>;
+
+/// The interval at which the time epoch is re-based, a power of two so
+/// that re-basing is exact (seconds)
+const TIME_EPOCH_STEP: f64 = 4096.0;
+
+/// A snapshot tagged with when it was inserted into the buffer
+#[derive(Clone)]
+pub(crate) struct Buffered<T> {
+    pub(crate) snapshot: T,
+    pub(crate) inserted: Instant,
+
+    /// Added to the snapshot's remote time, see
+    /// `Buffer::insert_snapshot_with_offset` (seconds)
+    pub(crate) time_offset: f64,
+}
+
+impl<T: Snapshot> Buffered<T> {
+    fn remote_time(&self) -> f64 {
+        self.snapshot.remote_time() + self.time_offset
+    }
+}
+
+/// The latest raw times between packets, newest first, in a ring of fixed
+/// capacity. Each delta is written twice, `capacity` apart, so that the
+/// latest are always contiguous from the head.
+#[derive(Clone)]
+struct RecentDeltas {
+    values: Vec<f64>,
+    head: usize,
+    len: usize,
+}
+
+impl RecentDeltas {
+    fn new(capacity: usize) -> Self {
+        Self {
+            values: vec![0.0; capacity * 2],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        self.values.len() / 2
+    }
+
+    /// Record the newest delta, forgetting the oldest once full
+    fn push(&mut self, delta: f64) {
+        let capacity = self.capacity();
+        if capacity == 0 {
+            return;
+        }
+        self.head = (self.head + capacity - 1) % capacity;
+        self.values[self.head] = delta;
+        self.values[self.head + capacity] = delta;
+        self.len = (self.len + 1).min(capacity);
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    fn as_slice(&self) -> &[f64] {
+        &self.values[self.head..self.head + self.len]
+    }
+}
+
+/// Blends from the last output of a previous stream toward the output of
+/// a newly joined stream, or from a spawn point, see `Playback::handover`
+/// and `Playback::ease_in_from`.
+struct Handover<T> {
+    from: T,
+
+    /// (seconds)
+    duration: f64,
+    elapsed: f64,
+}
+
+/// The furthest `t` extrapolated to beyond the newest segment, by default
+const MAX_EXTRAPOLATION_T: f64 = 2.5;
+
+/// What a playback outputs once starved of snapshots for longer than its
+/// extrapolation policy allows, i.e. it has extrapolated as far as
+/// `ExtrapolationOverride::max_t` or `max_periods`, or reached the newest
+/// snapshot without extrapolation allowed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Underrun {
+    /// Hold the furthest output, until snapshots arrive
+    Hold,
+
+    /// Output nothing, e.g. to hide an entity which has stopped updating,
+    /// until snapshots arrive
+    Hide,
+}
+
+/// A playback's own extrapolation policy, in place of the shared
+/// `Settings`, see `Playback::set_extrapolation_override`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExtrapolationOverride {
+    /// Whether to extrapolate beyond the newest snapshot at all, rather
+    /// than hold it while starved
+    pub allow: bool,
+
+    /// The furthest `t` to extrapolate to beyond the newest segment, at
+    /// most 2.5
+    pub max_t: f64,
+
+    /// What to output once starved beyond the above
+    pub underrun: Underrun,
+
+    /// In place of `Settings::max_extrapolation_periods`
+    pub max_periods: Option<f32>,
+
+    /// In place of `Settings::extrapolation_decay`
+    pub decay: f64,
+}
+
+impl ExtrapolationOverride {
+    /// The policy the shared `settings` give, to adjust from
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            allow: true,
+            max_t: MAX_EXTRAPOLATION_T,
+            underrun: Underrun::Hold,
+            max_periods: settings.max_extrapolation_periods,
+            decay: settings.extrapolation_decay,
+        }
+    }
+}
+
+/// Which bound of the clamp about the target time was hit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClampBound {
+    /// The playback was too far behind the target time
+    Min,
+
+    /// The playback was too far ahead of the target time
+    Max,
+}
+
+/// A clamp applied to the playback time, see `Playback::last_clamp`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClampEvent {
+    pub bound: ClampBound,
+
+    /// How far the playback time was moved (seconds)
+    pub amount: f64,
+}
+
+/// How the playback's timescale is correcting, see `StepStatus`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScalingState {
+    /// Slowed to let the buffer fill
+    Slow,
+
+    /// Playing at the remote's rate
+    Normal,
+
+    /// Hastened to catch up with the target time
+    Fast,
+}
+
+/// A complete picture of a single step, see `Playback::step_status`
+#[derive(Clone, Debug, PartialEq)]
+pub struct StepStatus<T> {
+    /// The interpolated snapshot, as returned by `step`
+    pub value: Option<T>,
+
+    /// There wasn't a snapshot beyond the playback time to interpolate
+    /// toward
+    pub extrapolating: bool,
+
+    /// The playback time was clamped about the target time, see
+    /// `Playback::last_clamp`
+    pub clamped: bool,
+
+    /// Waiting on data, either for the buffer to be primed or for a pair
+    /// of snapshots to interpolate between
+    pub stalled: bool,
+
+    pub scaling: ScalingState,
 }
 
+/// A custom interpolation function, see `Playback::with_interpolate_fn`
+pub type InterpolateFn<T> = Box<dyn Fn(f64, &T, &T) -> T + Send>;
+
+/// Called with the segment index and the threshold crossed, see
+/// `Playback::with_progress_callback`
+pub type ProgressFn = Box<dyn FnMut(usize, f64) + Send>;
+
 /// Playsback buffered snapshots in steady time, accelerating and
 /// deccelerating the local timescale in order to stay in tune with
 /// the remote remote timescale, also accounting for network jitter.
@@ -40,15 +276,104 @@ pub struct Playback<T> {
     settings: &'static Settings,
     _phantom: PhantomData<T>,
 
+    /// Overrides `Snapshot::interpolate` for this playback
+    interpolate_fn: Option<InterpolateFn<T>>,
+
+    /// Reshapes the interpolation parameter, see `with_easing_curve`
+    easing_curve: Option<EasingCurve>,
+
+    /// See `set_extrapolation_override`
+    extrapolation_override: Option<ExtrapolationOverride>,
+
+    /// Whether to interpolate at a constant speed along the curve, see
+    /// `with_arc_length_reparam`
+    arc_length_reparam: bool,
+
+    /// Smooths the output, see `with_one_euro_filter`. The prototype is
+    /// cloned for each of `Snapshot::filter_values`.
+    one_euro_filter: Option<(OneEuroFilter, Vec<OneEuroFilter>)>,
+
+    /// The progress thresholds and the callback fired on crossing them,
+    /// see `with_progress_callback`
+    progress_callback: Option<(Vec<f64>, ProgressFn)>,
+
+    /// Overrides the built-in timescale computation for this playback
+    timescale_controller: Option<Box<dyn TimescaleController + Send>>,
+
+    /// Local time passed since the timescale was last computed (seconds)
+    timescale_dt: f64,
+
+    /// When `step_instant` was last called
+    last_step_instant: Option<Instant>,
+
+    /// The `now` given to `step_instant`, for the duration of that step,
+    /// which the wall clock is read as
+    step_now: Option<Instant>,
+
     remote_counter: u128,
 
-    /// Aims to be remote_time - BUF_OFFSET
+    /// Whether `playback_time` has been seeded from the first snapshot
+    seeded: bool,
+
+    /// The epoch which `playback_time` is measured from, following the
+    /// store's `SnapshotStore::time_epoch`
+    time_epoch: f64,
+
+    /// Aims to be remote_time - BUF_OFFSET, measured from `time_epoch`
     /// (seconds)
     pub playback_time: f64,
 
     /// Rate at which time passes in order to maintain
     pub timescale: f64,
 
+    /// The speed which `timescale` is ramping toward, see
+    /// `Settings::timescale_slew_per_second`
+    target_timescale: f64,
+
+    /// The net change in `playback_time` during the last step
+    last_advance: f64,
+
+    /// How long the target timescale has held since it last changed
+    /// (seconds), see `Settings::min_scaling_hold_periods`
+    timescale_held: f64,
+
+    /// The distance left to converge onto the clamp, and the rate at which
+    /// to converge (seconds, seconds per second), see
+    /// `Settings::soft_clamp_periods`
+    soft_clamp: Option<(f64, f64)>,
+
+    /// The part of `last_advance` which seeded the playback time, rather
+    /// than being played through, see `effective_remote_dt`
+    seed_advance: f64,
+
+    /// The clamp applied during the last step, if any
+    last_clamp: Option<ClampEvent>,
+
+    /// Snapshots passed over without being played during the last step
+    last_skipped_count: usize,
+
+    /// The output of the last step
+    last_output: Option<T>,
+
+    /// The segment index and interpolation parameter of the last step
+    last_segment_progress: Option<(usize, f64)>,
+
+    /// The remote time of the snapshot the last step's segment starts
+    /// from, identifying the segment as snapshots arrive (seconds)
+    last_segment_from_time: f64,
+
+    /// The output last returned by `step_on_change`
+    last_changed_output: Option<T>,
+
+    /// An in progress blend from another stream, see `handover`
+    handover: Option<Handover<T>>,
+
+    /// The remote time between the snapshots last interpolated between,
+    /// used in place of the configured period to scale the slow/fast
+    /// thresholds when the remote's send interval isn't uniform.
+    /// (seconds)
+    segment_period: f64,
+
     /// Measure any drift between the local timescale and the remote timescale,
     /// in order to accelerate/deccelerate the local timescale to get back on
     /// track.
@@ -57,6 +382,13 @@ pub struct Playback<T> {
     /// and the targetted playback time (x periods behind the remote time)
     pub catchup_time: ExponentialMovingAverage,
 
+    /// How much the catchup time can be trusted, between 1.0 - packets
+    /// arriving every period, and 0.0 - long gaps between arrivals, over
+    /// the last second. Each arrival's confidence decays exponentially
+    /// with every period it arrives late, e.g. from packet loss. See
+    /// `Settings::catchup_loss_confidence`.
+    pub catchup_confidence: ExponentialMovingAverage,
+
     /// A debugging measure of how much the last 10 seconds
     /// have relied on extrapolation, between 1.0 - all, and
     /// 0.0 - none. (None is healthy)
@@ -71,17 +403,52 @@ pub struct Playback<T> {
     /// have relied on time scaling, between 1.0 - all, and
     /// 0.0 - none. (None is healthy, some is expected)
     pub db_scaling_ema: ExponentialMovingAverage,
+
+    /// A debugging measure of how many of the last 10 seconds worth of
+    /// steps were given a negative or excessive delta time, between
+    /// 1.0 - all, and 0.0 - none. (None is healthy)
+    pub db_delta_clamping_ema: ExponentialMovingAverage,
+
+    /// A debugging measure of how long snapshots dwell in the buffer
+    /// between being inserted and the playback time reaching them, over
+    /// the last 10 seconds (seconds). Short is a risk of starvation, long
+    /// is unnecessary latency.
+    pub dwell_time: ExponentialMovingAverage,
+
+    /// The local time between steps over the last 30 frames (seconds),
+    /// accounted for by `target_playback_time`
+    pub frame_time: ExponentialMovingAverage,
+
+    /// The remote time of the newest snapshot the playback time has reached
+    last_played_time: Option<f64>,
+
+    /// The last interpolation, and the time since (seconds), held between
+    /// interpolations by `Settings::max_interp_hz`
+    held_interpolation: Option<(Option<T>, f64)>,
 }
 
 impl<T: Snapshot> Buffer<T> {
     pub fn new(settings: &'static Settings) -> Self {
+        Self::new_in(settings, Global)
+    }
+}
+
+impl<T: Snapshot, A: Allocator> Buffer<T, A> {
+    /// Construct a buffer which stores its snapshots with `alloc` rather
+    /// than the global allocator, e.g. an arena allocated up front. Room
+    /// for `Settings::buf_len` snapshots is allocated here, however the
+    /// buffer may grow past it, see `Settings::auto_buffer` and `restore`.
+    /// Allocators other than `Global` need the nightly only
+    /// `allocator_api` feature.
+    pub fn new_in(settings: &'static Settings, alloc: A) -> Self {
         let send_rate = settings.send_rate();
-        let buf_len = (send_rate as f32 * settings.buf_duration).ceil() as usize;
+        let buf_len = settings.buf_len();
 
         Self {
             settings,
 
-            buf: VecDeque::with_capacity(buf_len),
+            // Room for the snapshot inserted before the oldest is evicted
+            buf: Deque::with_capacity_in(buf_len + 1, alloc),
             buf_len,
 
             last_remote_time: 0.0,
@@ -91,61 +458,457 @@ impl<T: Snapshot> Buffer<T> {
             remote_delta_time: ExponentialMovingAverage::new(
                 send_rate * settings.dynamic_playback_jitter_duration as f64,
             ),
+            remote_delta_samples: 0,
+            remote_delta_spikes: 0,
+            recent_deltas: RecentDeltas::new(settings.recent_deltas_len),
+            frozen_offset: None,
+            time_epoch: 0.0,
+            monotonic_violations: 0,
+            validator: None,
+            rejected_snapshots: 0,
+            time_offsets: false,
         }
     }
 
+    /// The latest raw times between packets in remote time, newest first,
+    /// up to `Settings::recent_deltas_len` of them (seconds). Unlike the
+    /// smoothed `remote_delta_time`, reveals patterns such as every other
+    /// packet arriving late.
+    pub fn recent_deltas(&self) -> &[f64] {
+        self.recent_deltas.as_slice()
+    }
+
     /// Retrieve the latest snapshot
     pub fn latest(&self) -> Option<&T> {
-        self.buf.front()
+        self.buf.front().map(|b| &b.snapshot)
+    }
+
+    /// How full the buffer is, between 0.0 - empty, and 1.0 - full
+    pub fn fill_ratio(&self) -> f64 {
+        self.buf.len() as f64 / self.buf_len as f64
+    }
+
+    /// Iterate over the buffered snapshots, newest first
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.buf.iter().map(|b| &b.snapshot)
+    }
+
+    /// Iterate over the buffered snapshots, oldest first, in the order
+    /// they're played back. Doesn't allocate.
+    pub fn iter_chronological(&self) -> impl Iterator<Item = &T> {
+        self.buf.iter().rev().map(|b| &b.snapshot)
+    }
+
+    /// The total `Snapshot::approx_size` of the buffered snapshots (bytes)
+    pub fn approx_bytes(&self) -> usize {
+        self.iter().map(Snapshot::approx_size).sum()
+    }
+
+    /// Save the buffer's snapshots and measurements, e.g. to persist a
+    /// session. The settings and any validator aren't saved.
+    pub fn save(&self) -> BufferState<T> {
+        let anchor = Instant::now();
+        BufferState {
+            snapshots: self
+                .buf
+                .iter()
+                .map(|b| {
+                    let age = crate::state::age(anchor, b.inserted);
+                    (b.snapshot.clone(), age, b.time_offset)
+                })
+                .collect(),
+            buf_len: self.buf_len,
+            last_remote_time: self.last_remote_time,
+            last_remote_age: crate::state::age(anchor, self.last_remote_instant),
+            last_remote_counter: self.last_remote_counter,
+            remote_delta_time: self.remote_delta_time.clone(),
+            remote_delta_samples: self.remote_delta_samples,
+            remote_delta_spikes: self.remote_delta_spikes,
+            recent_deltas: self.recent_deltas.as_slice().to_vec(),
+            frozen_offset: self.frozen_offset,
+            time_epoch: self.time_epoch,
+            monotonic_violations: self.monotonic_violations,
+            rejected_snapshots: self.rejected_snapshots,
+        }
+    }
+
+    /// Resume from a `save`d state, as if no time had passed since saving.
+    /// The buffer's own settings and validator are kept. May allocate
+    /// should the saved buffer be larger.
+    pub fn restore(&mut self, state: BufferState<T>) {
+        let anchor = Instant::now();
+        self.buf.clear();
+        self.buf.extend(
+            state
+                .snapshots
+                .into_iter()
+                .map(|(snapshot, age, time_offset)| Buffered {
+                    snapshot,
+                    inserted: crate::state::reanchor(anchor, age),
+                    time_offset,
+                }),
+        );
+        self.time_offsets = self.buf.iter().any(|b| b.time_offset != 0.0);
+        self.buf_len = state.buf_len;
+        self.last_remote_time = state.last_remote_time;
+        self.last_remote_instant = crate::state::reanchor(anchor, state.last_remote_age);
+        self.last_remote_counter = state.last_remote_counter;
+        self.remote_delta_time = state.remote_delta_time;
+        self.remote_delta_samples = state.remote_delta_samples;
+        self.remote_delta_spikes = state.remote_delta_spikes;
+        self.recent_deltas.clear();
+        for &delta in state.recent_deltas.iter().rev() {
+            self.recent_deltas.push(delta);
+        }
+        self.frozen_offset = state.frozen_offset;
+        self.time_epoch = state.time_epoch;
+        self.monotonic_violations = state.monotonic_violations;
+        self.rejected_snapshots = state.rejected_snapshots;
     }
 
     /// Insert a new snapshot from the net
-    pub fn insert_snapshot(&mut self, snapshot: T) {
+    ///
+    /// Returns the oldest snapshot if it was evicted to make room in the
+    /// buffer. This may be the inserted snapshot itself if it arrived too
+    /// late to ever be interpolated, or rejected by the validator. Should
+    /// `Settings::max_buffer_bytes` evict several, only the oldest is
+    /// returned.
+    pub fn insert_snapshot(&mut self, snapshot: T) -> Option<T> {
+        self.insert_snapshot_received_at(snapshot, Instant::now())
+    }
+
+    /// Insert as with `insert_snapshot`, as if the snapshot arrived at the
+    /// local instant `received` rather than now, e.g. when replaying a
+    /// captured network trace. Step with `Playback::step_instant` at the
+    /// recorded frame instants to reproduce the original run exactly.
+    pub fn insert_snapshot_received_at(&mut self, snapshot: T, received: Instant) -> Option<T> {
+        self.insert_buffered(snapshot, received, 0.0)
+    }
+
+    /// Insert as with `insert_snapshot`, treating the snapshot's remote
+    /// time as `time_offset` seconds later, without altering the snapshot.
+    /// e.g. to align sources with known fixed lags (one sensor trailing
+    /// another by 20ms) onto one timeline. The offset applies wherever the
+    /// buffer and playback order or bracket by remote time, see
+    /// `SnapshotStore::remote_time_of`.
+    pub fn insert_snapshot_with_offset(&mut self, snapshot: T, time_offset: f64) -> Option<T> {
+        self.insert_buffered(snapshot, Instant::now(), time_offset)
+    }
+
+    fn insert_buffered(&mut self, snapshot: T, received: Instant, time_offset: f64) -> Option<T> {
+        // 0. Keep corrupt data out of the buffer entirely
+        if let Some(validator) = &self.validator
+            && !validator(&snapshot)
+        {
+            self.rejected_snapshots += 1;
+            return Some(snapshot);
+        }
+
+        // 1. Size the buffer to the measured jitter
+        if self.settings.auto_buffer {
+            self.buf_len = self.auto_buf_len();
+        }
+
         // 2. Insert snapshot
-        self.insert(snapshot);
+        self.time_offsets |= time_offset != 0.0;
+        let last_front_time = self.buf.front().map(Buffered::remote_time);
+        let evicted = self.insert(Buffered {
+            snapshot,
+            inserted: received,
+            time_offset,
+        });
 
         let mut buf_iter = self.buf.iter();
         if let Some(ss_to) = buf_iter.next() {
-            // 3. Add snapshot delta time to moving average
-            // (Assumes that the received snapshot went to the front of the buf)
-            if let Some(ss_from) = buf_iter.next() {
+            // 3. Add snapshot delta time to moving average, only should the
+            // received snapshot have gone to the front of the buf, rather
+            // than been skipped as a duplicate, arrived late or been evicted
+            let received_newest = last_front_time.is_none_or(|time| ss_to.remote_time() > time);
+            if let Some(ss_from) = buf_iter.next().filter(|_| received_newest) {
                 let delta_time = ss_to.remote_time() - ss_from.remote_time();
-                self.remote_delta_time.add(delta_time);
+                self.recent_deltas.push(delta_time);
+                if Self::is_jitter_spike(
+                    self.settings,
+                    &self.remote_delta_time,
+                    self.remote_delta_spikes,
+                    delta_time,
+                ) {
+                    self.remote_delta_spikes += 1;
+                } else {
+                    self.remote_delta_time.add(delta_time);
+                    self.remote_delta_samples += 1;
+                    self.remote_delta_spikes = 0;
+                }
             }
 
-            self.last_remote_instant = Instant::now();
+            self.last_remote_instant = received;
             self.last_remote_time = ss_to.remote_time();
+
+            // Periodically re-base the epoch to keep up with the remote time
+            let time_epoch = (ss_to.remote_time() / TIME_EPOCH_STEP).floor() * TIME_EPOCH_STEP;
+            self.time_epoch = self.time_epoch.max(time_epoch);
             self.last_remote_counter = self.last_remote_counter.wrapping_add(1);
         }
+
+        if self.settings.adaptive_ema_window {
+            self.resize_remote_delta_window();
+        }
+
+        // Network conditions have drastically changed since freezing
+        if let Some((_, frozen_std_dev)) = self.frozen_offset
+            && let Some(unfreeze) = self.settings.offset_unfreeze()
+            && (self.remote_delta_time.std_dev - frozen_std_dev).abs() > unfreeze as f64
+        {
+            self.frozen_offset = None;
+        }
+
+        evicted
+    }
+
+    /// Whether a time between packets is an outlier which should be
+    /// excluded from the jitter measurement, see `Settings::jitter_spike_reset`
+    fn is_jitter_spike(
+        settings: &Settings,
+        remote_delta_time: &ExponentialMovingAverage,
+        remote_delta_spikes: usize,
+        delta_time: f64,
+    ) -> bool {
+        const MAX_CONSECUTIVE_SPIKES: usize = 3;
+
+        let (Some(std_devs), Some(mean)) = (settings.jitter_spike_reset, remote_delta_time.value)
+        else {
+            return false;
+        };
+
+        remote_delta_spikes < MAX_CONSECUTIVE_SPIKES
+            && remote_delta_time.std_dev > 0.0
+            && (delta_time - mean).abs() > std_devs as f64 * remote_delta_time.std_dev
+    }
+
+    /// Reject any snapshot failing `validator` on insertion, counting it in
+    /// `rejected_snapshots`, e.g. to enforce domain invariants such as a
+    /// position within the world's bounds. Rejected snapshots are never
+    /// interpolated nor measured for jitter.
+    pub fn set_validator(&mut self, validator: impl Fn(&T) -> bool + Send + Sync + 'static) {
+        self.validator = Some(Arc::new(validator));
+    }
+
+    /// The number of snapshots the buffer holds before evicting the oldest
+    pub fn max_len(&self) -> usize {
+        self.buf_len
+    }
+
+    /// The buffer length which covers the dynamic playback offset plus
+    /// the margin, see `Settings::auto_buffer`
+    fn auto_buf_len(&self) -> usize {
+        let duration = self.dynamic_playback_offset() + self.settings.auto_buffer_margin() as f64;
+        // Tolerate float noise, e.g. from the settings' f32 periods
+        let periods = duration / self.settings.period - 1e-3;
+        (periods.ceil() as usize).clamp(2, self.settings.max_buf_len.max(2))
+    }
+
+    /// Once a full window of samples has been measured, re-derive the
+    /// jitter window from the measured send rate.
+    fn resize_remote_delta_window(&mut self) {
+        if (self.remote_delta_samples as f64) < self.remote_delta_time.window() {
+            return;
+        }
+        self.remote_delta_samples = 0;
+
+        if let Some(mean_delta) = self.remote_delta_time.value
+            && mean_delta > 0.0
+        {
+            let send_rate = 1.0 / mean_delta;
+            self.remote_delta_time
+                .set_window(send_rate * self.settings.dynamic_playback_jitter_duration as f64);
+        }
+    }
+
+    /// Remove snapshots older than the given remote time (seconds), e.g. a
+    /// margin behind the playback time, returning how many were removed.
+    /// The newest snapshot older than `remote_time` is kept, so that the
+    /// pair bracketing `remote_time` survives, as are at least two
+    /// snapshots.
+    pub fn prune_before(&mut self, remote_time: f64) -> usize {
+        let Some(ss_from_pos) = self.buf.iter().position(|b| b.remote_time() < remote_time) else {
+            return 0;
+        };
+
+        let keep = (ss_from_pos + 1).max(2);
+        let removed = self.buf.len().saturating_sub(keep);
+        self.buf.truncate(keep);
+        removed
+    }
+
+    /// Estimate the remote's current clock, i.e. the remote time of the
+    /// latest snapshot plus the time which has passed locally since it
+    /// arrived. Useful for timestamping local actions. This is not the
+    /// playback clock, which lags behind by the playback offset.
+    /// (seconds)
+    pub fn estimated_remote_now(&self) -> f64 {
+        self.estimated_remote_now_at(Instant::now())
+    }
+
+    /// Estimate the remote's clock as with `estimated_remote_now`, as of
+    /// the local instant `now` (seconds)
+    pub fn estimated_remote_now_at(&self, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.last_remote_instant);
+        self.last_remote_time + elapsed.as_secs_f64()
+    }
+
+    /// Interpolate the buffer exactly at a remote time (seconds), without
+    /// any playback clock, e.g. at the authoritative timestamp of an input
+    /// for rewind netcode. Returns the output, the `t` interpolated at, and
+    /// the positions (newest first, as with `iter`) of the snapshots
+    /// interpolated from and to. `None` if the buffer can't bracket the
+    /// time, i.e. it's older than every snapshot, or there's only one.
+    pub fn sample_at_detailed(&self, remote_time: f64) -> Option<(T, f64, usize, usize)> {
+        let (Some((from_pos, to_pos)), _) = bracket_positions(remote_time, 0.0, self) else {
+            return None;
+        };
+        let (from, to) = (&self.buf[from_pos], &self.buf[to_pos]);
+        let mut t = linear_map(remote_time, from.remote_time(), to.remote_time(), 0.0, 1.0)
+            .clamp(0.0, MAX_EXTRAPOLATION_T);
+
+        let (ss_from, ss_to) = (&from.snapshot, &to.snapshot);
+
+        if !can_blend(ss_from, ss_to) {
+            return Some((ss_to.clone(), t, from_pos, to_pos));
+        }
+
+        let mut output = if t > 1.0 && !T::EXTRAPOLATABLE {
+            // Hold the newest snapshot rather than extrapolate it
+            t = 1.0;
+            ss_to.clone()
+        } else {
+            T::interpolate(t, ss_from, ss_to)
+        };
+        output.clamp();
+        Some((output, t, from_pos, to_pos))
+    }
+
+    /// How long ago the newest snapshot arrived, in wall-clock time,
+    /// regardless of how much playable content remains buffered. Useful
+    /// for detecting a dead connection. Measured from construction until
+    /// the first snapshot arrives. (seconds)
+    pub fn data_age(&self) -> f64 {
+        self.data_age_at(Instant::now())
+    }
+
+    /// How long before the local instant `now` the newest snapshot
+    /// arrived, as with `data_age`, e.g. for replays (seconds)
+    pub fn data_age_at(&self, now: Instant) -> f64 {
+        now.saturating_duration_since(self.last_remote_instant)
+            .as_secs_f64()
     }
 
     /// Compute the playback offset dynamically to adjust for
     /// measured network jitter. Exposed publically for debugging.
     /// (seconds)
     pub fn dynamic_playback_offset(&self) -> f64 {
+        if let Some((frozen_offset, _)) = self.frozen_offset {
+            return frozen_offset;
+        }
+
         let playback_offset = self.settings.playback_offset() as f64;
 
         if self.settings.dynamic_playback_time {
             // Account for recent network jitter
-            playback_offset + self.remote_delta_time.std_dev
+            let offset = playback_offset + self.remote_delta_time.std_dev;
+            match self.settings.offset_quantum_ms {
+                Some(quantum_ms) if quantum_ms > 0.0 => {
+                    let quantum = quantum_ms as f64 / 1000.0;
+                    (offset / quantum).round() * quantum
+                }
+                _ => offset,
+            }
         } else {
             playback_offset
         }
     }
 
+    /// The playback offset (seconds) at which the chance of the next
+    /// snapshot not having arrived by the time it's needed, i.e. of
+    /// extrapolating, is `target_extrapolation_prob`, assuming the time
+    /// between packets is normally distributed as measured. Lower targets
+    /// cost latency. The configured playback offset until measured.
+    pub fn optimal_offset(&self, target_extrapolation_prob: f64) -> f64 {
+        let Some(mean) = self.remote_delta_time.value else {
+            return self.settings.playback_offset() as f64;
+        };
+
+        let prob = target_extrapolation_prob.clamp(1e-9, 1.0 - 1e-9);
+        let z = -inverse_normal_cdf(prob);
+        (mean + z * self.remote_delta_time.std_dev).max(0.0)
+    }
+
+    /// Latch the current `dynamic_playback_offset`, e.g. once measured at
+    /// connection, for a steady latency at the cost of adapting to the
+    /// network. Jitter is still measured meanwhile, see
+    /// `Settings::offset_unfreeze_periods`.
+    pub fn freeze_offset(&mut self) {
+        self.frozen_offset = Some((
+            self.dynamic_playback_offset(),
+            self.remote_delta_time.std_dev,
+        ));
+    }
+
+    /// Resume adapting the playback offset to the measured jitter
+    pub fn unfreeze_offset(&mut self) {
+        self.frozen_offset = None;
+    }
+
+    /// Whether the playback offset is latched, see `freeze_offset`
+    pub fn is_offset_frozen(&self) -> bool {
+        self.frozen_offset.is_some()
+    }
+
+    /// The position (newest first) of a snapshot borrowed from the buffer,
+    /// found from its address rather than by searching, so that remote
+    /// times can be looked up in constant time. `None` if it wasn't
+    /// borrowed from the buffer.
+    fn position_of(&self, snapshot: &T) -> Option<usize> {
+        let size = size_of::<Buffered<T>>();
+        let address = snapshot as *const T as usize;
+
+        let (front, back) = self.buf.as_slices();
+        let mut skipped = 0;
+        for slice in [front, back] {
+            let start = slice.as_ptr() as usize + std::mem::offset_of!(Buffered<T>, snapshot);
+            let offset = address.wrapping_sub(start);
+            if address >= start && offset < size_of_val(slice) && offset.is_multiple_of(size) {
+                return Some(skipped + offset / size);
+            }
+            skipped += slice.len();
+        }
+        None
+    }
+
     /// Insert a snapshot into the buffer, maintaining the buffer size,
-    /// the correct order and skipping duplicates.
-    fn insert(&mut self, item: T) {
-        if self
+    /// the correct order and handling duplicates. Returns the evicted
+    /// snapshot, if any.
+    fn insert(&mut self, item: Buffered<T>) -> Option<T> {
+        if let Some(existing) = self
             .buf
-            .iter()
-            .any(|b| b.remote_time() == item.remote_time())
+            .iter_mut()
+            .find(|b| b.remote_time() == item.remote_time())
         {
-            //Skip duplicates
-            // tracing::debug!("skipping duplicate position");
-            return;
+            match self.settings.on_time_collision {
+                TimeCollision::KeepExisting => {
+                    //Skip duplicates
+                    // tracing::debug!("skipping duplicate position");
+                }
+                TimeCollision::ReplaceWithNew => *existing = item,
+            }
+            return None;
         }
 
+        let remote_time = item.remote_time();
+        let out_of_order = self
+            .buf
+            .front()
+            .is_some_and(|newest| remote_time < newest.remote_time());
+
         if let Some(position) = self
             .buf
             .iter()
@@ -158,162 +921,1551 @@ impl<T: Snapshot> Buffer<T> {
             self.buf.push_back(item);
         }
 
-        if self.buf.len() > self.buf_len {
-            self.buf.pop_back();
+        let mut evicted = None;
+        while self.buf.len() > self.buf_len {
+            let Some(oldest) = self.buf.pop_back() else {
+                break;
+            };
+            evicted.get_or_insert(oldest.snapshot);
         }
-    }
-}
 
-impl<T: Clone> Clone for Buffer<T> {
-    fn clone(&self) -> Self {
-        Self {
-            settings: self.settings,
-            buf: self.buf.clone(),
-            buf_len: self.buf_len.clone(),
-            last_remote_time: self.last_remote_time.clone(),
-            last_remote_instant: self.last_remote_instant.clone(),
-            last_remote_counter: self.last_remote_counter.clone(),
-            remote_delta_time: self.remote_delta_time.clone(),
+        if let Some(max_buffer_bytes) = self.settings.max_buffer_bytes {
+            let mut bytes = self.approx_bytes();
+            while bytes > max_buffer_bytes && self.buf.len() > 1 {
+                let Some(oldest) = self.buf.pop_back() else {
+                    break;
+                };
+                bytes -= oldest.snapshot.approx_size();
+                evicted.get_or_insert(oldest.snapshot);
+            }
+        }
+
+        // Only count snapshots late enough to be out of order, but not so
+        // late that they were evicted straight away
+        if out_of_order
+            && self
+                .buf
+                .back()
+                .is_some_and(|oldest| oldest.remote_time() <= remote_time)
+        {
+            self.monotonic_violations += 1;
         }
+
+        evicted
     }
 }
 
-impl<T: Snapshot> Playback<T> {
-    pub fn new(buf: &Buffer<T>) -> Self {
-        let settings = buf.settings;
-        let send_rate = settings.send_rate();
+/// Whether two snapshots may be blended between, rather than snapped
+fn can_blend<T: Snapshot>(from: &T, to: &T) -> bool {
+    // Interpolating across schema versions is invalid, and across parents
+    // would slide an attachment between them
+    from.version() == to.version() && from.parent_id() == to.parent_id()
+}
 
-        Self {
-            settings,
-            _phantom: PhantomData,
+/// The standard normal quantile of `p` (between 0 and 1 exclusive), by
+/// the rational approximation of Abramowitz & Stegun 26.2.23, within
+/// 4.5e-4.
+fn inverse_normal_cdf(p: f64) -> f64 {
+    const C: [f64; 3] = [2.515517, 0.802853, 0.010328];
+    const D: [f64; 3] = [1.432788, 0.189269, 0.001308];
 
-            remote_counter: buf.last_remote_counter,
-            playback_time: 0.0,
-            timescale: 1.0,
+    let tail = p.min(1.0 - p);
+    let t = (-2.0 * tail.ln()).sqrt();
+    let z =
+        t - (C[0] + C[1] * t + C[2] * t * t) / (1.0 + D[0] * t + D[1] * t * t + D[2] * t * t * t);
+    if p < 0.5 { -z } else { z }
+}
 
-            catchup_time: ExponentialMovingAverage::new(send_rate), // 1 seconds worth of duration,
-            db_extrapolating_ema: ExponentialMovingAverage::new(send_rate * 10.0), // 10 seconds worth of duration,
-            db_clamping_ema: ExponentialMovingAverage::new(send_rate * 10.0), // 10 seconds worth of duration,
-            db_scaling_ema: ExponentialMovingAverage::new(send_rate * 10.0), // 10 seconds worth of duration,
+/// Find the positions (newest first) of the snapshots between which to
+/// interpolate at the playback time (relative to `time_epoch`), and
+/// whether doing so requires extrapolating.
+///
+/// Segments are open at their start and closed at their end. A playback
+/// time exactly on a snapshot's remote time selects the segment ending at
+/// that snapshot, at a `t` of 1.0, never the segment starting there. So
+/// reaching the newest snapshot exactly isn't extrapolating, and the same
+/// playback time always selects the same segment.
+fn bracket_positions<T: Snapshot>(
+    playback_time: f64,
+    time_epoch: f64,
+    buf: &impl SnapshotStore<T>,
+) -> (Option<(usize, usize)>, bool) {
+    let relative_time = |b: &T| buf.remote_time_of(b) - time_epoch;
+
+    let ss_from_pos = buf.iter().position(|b| relative_time(b) < playback_time);
+    match ss_from_pos {
+        None => {
+            // There isn't any packet in the buffer which arrived before the playback time
+            (None, true)
+        }
+        Some(0) => {
+            // Extrapolate beyond the newest two
+            let positions = buf.get(1).is_some().then_some((1, 0));
+            if let (Some(ss_from), Some(ss_to)) = (buf.get(1), buf.get(0)) {
+                debug_assert!(playback_time >= relative_time(ss_from));
+                debug_assert!(playback_time >= relative_time(ss_to));
+            }
+            (positions, true)
+        }
+        Some(ss_from_pos) => {
+            let ss_to_pos = ss_from_pos - 1;
+            if let (Some(ss_from), Some(ss_to)) = (buf.get(ss_from_pos), buf.get(ss_to_pos)) {
+                debug_assert!(playback_time <= relative_time(ss_to));
+                debug_assert!(playback_time >= relative_time(ss_from));
+            }
+            (Some((ss_from_pos, ss_to_pos)), false)
         }
     }
+}
 
-    /// Draw a new interpolated snapshot by passing in how much time
-    /// has passed since the last step (seconds).
-    pub fn step(&mut self, delta_time: f64, buf: &Buffer<T>) -> Option<T> {
-        let playback_offset = buf.dynamic_playback_offset();
-        let playback_clamp = self.settings.playback_clamp() as f64;
+impl<T: Snapshot, A: Allocator> SnapshotStore<T> for Buffer<T, A> {
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a T>
+    where
+        T: 'a,
+    {
+        Buffer::iter(self)
+    }
 
-        // 1. Step playback time
-        self.playback_time += delta_time * self.timescale;
+    fn front(&self) -> Option<&T> {
+        self.latest()
+    }
 
-        // 2. Find the packets between which to interpolate (for later)
-        let ss_from_pos = buf
-            .buf
-            .iter()
-            .position(|b| b.remote_time() < self.playback_time);
-        let mut extrapolating = 0.0;
-        let snapshots = match ss_from_pos {
+    fn back(&self) -> Option<&T> {
+        self.buf.back().map(|b| &b.snapshot)
+    }
+
+    fn get(&self, index: usize) -> Option<&T> {
+        self.buf.get(index).map(|b| &b.snapshot)
+    }
+
+    fn arrival_counter(&self) -> u128 {
+        self.last_remote_counter
+    }
+
+    fn remote_time_of(&self, snapshot: &T) -> f64 {
+        if !self.time_offsets {
+            return snapshot.remote_time();
+        }
+        match self.position_of(snapshot) {
+            Some(position) => self.buf[position].remote_time(),
             None => {
-                // There isn't any packet in the buffer which arrived before the playback time
-                extrapolating = 1.0;
-                None
+                debug_assert!(false, "snapshot wasn't borrowed from this buffer");
+                snapshot.remote_time()
             }
-            Some(0) => {
-                extrapolating = 1.0;
+        }
+    }
 
-                let ss_to = buf.buf.get(0);
-                let ss_from = buf.buf.get(1);
-                match (ss_from, ss_to) {
-                    (Some(ss_from), Some(ss_to)) => {
-                        debug_assert!(self.playback_time >= ss_from.remote_time());
-                        debug_assert!(self.playback_time >= ss_to.remote_time());
+    fn estimated_remote_now(&self) -> f64 {
+        Buffer::estimated_remote_now(self)
+    }
 
-                        Some((ss_from, ss_to))
-                    }
-                    _ => None,
-                }
-            }
-            Some(ss_from_pos) => {
-                let ss_to_pos = ss_from_pos - 1;
-                let ss_from = buf.buf.get(ss_from_pos);
-                let ss_to = buf.buf.get(ss_to_pos);
-                match (ss_from, ss_to) {
-                    (Some(ss_from), Some(ss_to)) => {
-                        debug_assert!(self.playback_time <= ss_to.remote_time());
-                        debug_assert!(self.playback_time >= ss_from.remote_time());
-
-                        Some((ss_from, ss_to))
-                    }
-                    _ => None,
-                }
+    fn estimated_remote_now_at(&self, now: Instant) -> f64 {
+        Buffer::estimated_remote_now_at(self, now)
+    }
+
+    fn playback_offset(&self, _settings: &Settings) -> f64 {
+        self.dynamic_playback_offset()
+    }
+
+    fn time_epoch(&self) -> f64 {
+        self.time_epoch
+    }
+
+    fn inserted_at(&self, index: usize) -> Option<Instant> {
+        self.buf.get(index).map(|b| b.inserted)
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone> Clone for Buffer<T, A> {
+    fn clone(&self) -> Self {
+        Self {
+            settings: self.settings,
+            buf: self.buf.clone(),
+            buf_len: self.buf_len,
+            last_remote_time: self.last_remote_time,
+            last_remote_instant: self.last_remote_instant,
+            last_remote_counter: self.last_remote_counter,
+            remote_delta_time: self.remote_delta_time.clone(),
+            remote_delta_samples: self.remote_delta_samples,
+            remote_delta_spikes: self.remote_delta_spikes,
+            recent_deltas: self.recent_deltas.clone(),
+            frozen_offset: self.frozen_offset,
+            time_epoch: self.time_epoch,
+            monotonic_violations: self.monotonic_violations,
+            validator: self.validator.clone(),
+            rejected_snapshots: self.rejected_snapshots,
+            time_offsets: self.time_offsets,
+        }
+    }
+}
+
+impl<T: Snapshot> Playback<T> {
+    pub fn new<A: Allocator>(buf: &Buffer<T, A>) -> Self {
+        Self {
+            remote_counter: buf.last_remote_counter,
+            ..Self::with_settings(buf.settings)
+        }
+    }
+
+    /// Construct a playback without a `Buffer`, for playing back from
+    /// any other `SnapshotStore`.
+    pub fn with_settings(settings: &'static Settings) -> Self {
+        let send_rate = settings.send_rate();
+
+        Self {
+            settings,
+            _phantom: PhantomData,
+            interpolate_fn: None,
+            easing_curve: None,
+            extrapolation_override: None,
+            arc_length_reparam: false,
+            one_euro_filter: None,
+            progress_callback: None,
+            timescale_controller: None,
+            timescale_dt: 0.0,
+            last_step_instant: None,
+            step_now: None,
+
+            remote_counter: 0,
+            seeded: false,
+            time_epoch: 0.0,
+            playback_time: 0.0,
+            timescale: 1.0,
+            target_timescale: 1.0,
+            last_advance: 0.0,
+            seed_advance: 0.0,
+            soft_clamp: None,
+            timescale_held: f64::INFINITY,
+            last_clamp: None,
+            last_skipped_count: 0,
+            last_output: None,
+            last_segment_progress: None,
+            last_segment_from_time: f64::NAN,
+            last_changed_output: None,
+            handover: None,
+            segment_period: settings.period,
+
+            catchup_time: ExponentialMovingAverage::new(send_rate), // 1 seconds worth of duration,
+            catchup_confidence: ExponentialMovingAverage::new(send_rate), // 1 seconds worth of duration,
+            db_extrapolating_ema: ExponentialMovingAverage::new(send_rate * 10.0), // 10 seconds worth of duration,
+            db_clamping_ema: ExponentialMovingAverage::new(send_rate * 10.0), // 10 seconds worth of duration,
+            db_scaling_ema: ExponentialMovingAverage::new(send_rate * 10.0), // 10 seconds worth of duration,
+            db_delta_clamping_ema: ExponentialMovingAverage::new(send_rate * 10.0), // 10 seconds worth of duration,
+            dwell_time: ExponentialMovingAverage::new(send_rate * 10.0), // 10 seconds worth of duration,
+            frame_time: ExponentialMovingAverage::new(30.0), // 30 frames worth of duration,
+            last_played_time: None,
+            held_interpolation: None,
+        }
+    }
+
+    /// Save the playback's clock and measurements, e.g. to persist a
+    /// session alongside `Buffer::save`
+    pub fn save(&self) -> PlaybackState {
+        PlaybackState {
+            remote_counter: self.remote_counter,
+            seeded: self.seeded,
+            time_epoch: self.time_epoch,
+            playback_time: self.playback_time,
+            timescale: self.timescale,
+            target_timescale: self.target_timescale,
+            timescale_dt: self.timescale_dt,
+            segment_period: self.segment_period,
+            last_played_time: self.last_played_time,
+            catchup_time: self.catchup_time.clone(),
+            catchup_confidence: self.catchup_confidence.clone(),
+            db_extrapolating_ema: self.db_extrapolating_ema.clone(),
+            db_clamping_ema: self.db_clamping_ema.clone(),
+            db_scaling_ema: self.db_scaling_ema.clone(),
+            db_delta_clamping_ema: self.db_delta_clamping_ema.clone(),
+            dwell_time: self.dwell_time.clone(),
+            frame_time: self.frame_time.clone(),
+        }
+    }
+
+    /// Resume from a `save`d state, keeping this playback's customization.
+    /// Per step measurements (e.g. `last_clamp`) are cleared, and any in
+    /// progress handover or ease in is abandoned. The next `step_instant`
+    /// steps by zero, as if the first.
+    pub fn restore(&mut self, state: PlaybackState) {
+        self.remote_counter = state.remote_counter;
+        self.seeded = state.seeded;
+        self.time_epoch = state.time_epoch;
+        self.playback_time = state.playback_time;
+        self.timescale = state.timescale;
+        self.target_timescale = state.target_timescale;
+        self.timescale_dt = state.timescale_dt;
+        self.segment_period = state.segment_period;
+        self.last_played_time = state.last_played_time;
+        self.catchup_time = state.catchup_time;
+        self.catchup_confidence = state.catchup_confidence;
+        self.db_extrapolating_ema = state.db_extrapolating_ema;
+        self.db_clamping_ema = state.db_clamping_ema;
+        self.db_scaling_ema = state.db_scaling_ema;
+        self.db_delta_clamping_ema = state.db_delta_clamping_ema;
+        self.dwell_time = state.dwell_time;
+        self.frame_time = state.frame_time;
+
+        self.last_step_instant = None;
+        self.last_advance = 0.0;
+        self.seed_advance = 0.0;
+        self.last_clamp = None;
+        self.last_skipped_count = 0;
+        self.last_output = None;
+        self.last_segment_progress = None;
+        self.last_segment_from_time = f64::NAN;
+        self.last_changed_output = None;
+        self.handover = None;
+        self.held_interpolation = None;
+        self.soft_clamp = None;
+        self.timescale_held = f64::INFINITY;
+    }
+
+    /// Interpolate with `interpolate_fn` rather than `Snapshot::interpolate`,
+    /// e.g. to hold some fields while interpolating others. Allows multiple
+    /// playbacks over the same buffer to blend differently.
+    pub fn with_interpolate_fn(
+        mut self,
+        interpolate_fn: impl Fn(f64, &T, &T) -> T + Send + 'static,
+    ) -> Self {
+        self.interpolate_fn = Some(Box::new(interpolate_fn));
+        self
+    }
+
+    /// Reshape the interpolation parameter with `easing_curve` before
+    /// interpolating, e.g. for designer tuned feel. Applies to any
+    /// `interpolate_fn`, but not to extrapolation.
+    pub fn with_easing_curve(mut self, easing_curve: EasingCurve) -> Self {
+        self.easing_curve = Some(easing_curve);
+        self
+    }
+
+    /// Extrapolate according to `extrapolation_override` rather than the
+    /// shared `Settings`, e.g. so that projectiles extrapolate far while a
+    /// gauge over the same settings never does. `None` reverts to the
+    /// settings.
+    ///
+    /// Panics if the override's `max_t` is beyond 2.5, the furthest `t`
+    /// ever extrapolated to.
+    pub fn set_extrapolation_override(
+        &mut self,
+        extrapolation_override: Option<ExtrapolationOverride>,
+    ) {
+        if let Some(extrapolation_override) = extrapolation_override {
+            assert!(
+                (0.0..=MAX_EXTRAPOLATION_T).contains(&extrapolation_override.max_t),
+                "max_t must be within 0.0..={MAX_EXTRAPOLATION_T}"
+            );
+        }
+        self.extrapolation_override = extrapolation_override;
+    }
+
+    /// The extrapolation policy in effect, from any override, otherwise
+    /// the settings
+    pub fn extrapolation(&self) -> ExtrapolationOverride {
+        self.extrapolation_override
+            .unwrap_or_else(|| ExtrapolationOverride::from_settings(self.settings))
+    }
+
+    /// The furthest `t` to extrapolate to under the policy in effect
+    fn max_extrapolation_t(&self) -> f64 {
+        let extrapolation = self.extrapolation();
+        if extrapolation.allow {
+            extrapolation.max_t
+        } else {
+            1.0
+        }
+    }
+
+    /// Reparameterize each segment by `Snapshot::arc_length` so that the
+    /// output moves at a constant speed through it, rather than speeding
+    /// up and slowing down through the curves of
+    /// `Snapshot::interpolate_quadratic`. e.g. for vehicles following a
+    /// path. Samples the segment several times per step.
+    pub fn with_arc_length_reparam(mut self) -> Self {
+        self.arc_length_reparam = true;
+        self
+    }
+
+    /// Smooth each of the output's `Snapshot::filter_values` with a
+    /// one-euro filter, e.g. for noisy position data which remains
+    /// jittery even when interpolated.
+    pub fn with_one_euro_filter(mut self, filter: OneEuroFilter) -> Self {
+        self.one_euro_filter = Some((filter, Vec::new()));
+        self
+    }
+
+    /// Call `callback` with the segment index and threshold whenever the
+    /// playback crosses one of `thresholds` (between 0.0 exclusive and 1.0
+    /// inclusive) of a segment's progress moving forward, e.g. to fire
+    /// footsteps part way through a stride. See `last_segment_progress`.
+    pub fn with_progress_callback(
+        mut self,
+        thresholds: Vec<f64>,
+        callback: impl FnMut(usize, f64) + Send + 'static,
+    ) -> Self {
+        self.progress_callback = Some((thresholds, Box::new(callback)));
+        self
+    }
+
+    /// Compute the timescale with `controller` rather than the built-in
+    /// slow/normal/fast speeds, e.g. to experiment with PID control.
+    pub fn with_timescale_controller(
+        mut self,
+        controller: impl TimescaleController + Send + 'static,
+    ) -> Self {
+        self.timescale_controller = Some(Box::new(controller));
+        self
+    }
+
+    /// Draw a new interpolated snapshot by passing in how much time
+    /// has passed since the last step (seconds).
+    pub fn step(&mut self, delta_time: f64, buf: &impl SnapshotStore<T>) -> Option<T> {
+        self.step_status(delta_time, buf).value
+    }
+
+    /// Step as with `step`, also reporting how the playback fared during
+    /// the step in a single call, rather than querying each getter. The
+    /// recommended way to step.
+    pub fn step_status(&mut self, delta_time: f64, buf: &impl SnapshotStore<T>) -> StepStatus<T> {
+        let (value, snapshots, extrapolating) = self.step_snapshots(delta_time, buf);
+
+        let scaling = if self.timescale > 1.0 {
+            ScalingState::Fast
+        } else if self.timescale < 1.0 {
+            ScalingState::Slow
+        } else {
+            ScalingState::Normal
+        };
+
+        StepStatus {
+            value,
+            extrapolating,
+            clamped: self.last_clamp.is_some(),
+            stalled: !self.seeded || snapshots.is_none(),
+            scaling,
+        }
+    }
+
+    /// Step as with `step`, computing the delta time from the `now` given
+    /// to the previous call, rather than the caller keeping track. The
+    /// first call steps by zero. Absurd deltas are clamped as with `step`.
+    /// Time since snapshots arrived is measured up to `now` too, rather
+    /// than the wall clock, so that replays step identically.
+    pub fn step_instant(&mut self, now: Instant, buf: &impl SnapshotStore<T>) -> Option<T> {
+        let delta_time = match self.last_step_instant {
+            // A `now` before the previous steps by zero
+            Some(last) => now.saturating_duration_since(last).as_secs_f64(),
+            None => 0.0,
+        };
+        self.last_step_instant = self.last_step_instant.max(Some(now));
+        self.step_now = Some(now);
+        let output = self.step(delta_time, buf);
+        self.step_now = None;
+        output
+    }
+
+    /// Jump the playback directly to `remote_time` (seconds, as given by
+    /// `Snapshot::remote_time`, not measured from `time_epoch`) and
+    /// interpolate, bypassing the catchup and timescale. For presenting
+    /// the same playback across clients from a synchronized clock.
+    pub fn step_to(&mut self, remote_time: f64, buf: &impl SnapshotStore<T>) -> Option<T> {
+        let step = |play: &mut Self| {
+            play.follow_time_epoch(buf);
+
+            let start_playback_time = play.playback_time;
+            play.playback_time = remote_time - play.time_epoch;
+            play.last_advance = play.playback_time - start_playback_time;
+            play.seed_advance = if play.seeded { 0.0 } else { play.last_advance };
+            play.seeded = true;
+            play.last_clamp = None;
+            play.last_skipped_count = play.count_skipped(start_playback_time, buf);
+
+            let playback_time = play.sampling_time(play.playback_time);
+            let (snapshots, extrapolating) = Self::bracket(playback_time, play.time_epoch, buf);
+            (play.last_advance.abs(), snapshots, extrapolating)
+        };
+        let interpolate = |play: &Self, playback_time, snapshots| {
+            Self::interpolate(
+                playback_time,
+                play.time_epoch,
+                snapshots,
+                buf,
+                |t, prev, from, to| play.blend_segment(t, prev, from, to),
+            )
+        };
+        self.play(buf, step, interpolate).0
+    }
+
+    /// Step the playback backward through the buffered history by
+    /// `delta_time` (seconds) at real time, e.g. for scrubbing a replay.
+    /// The catchup and clamping are disabled while reversing, and the
+    /// playback time never rewinds beyond the oldest buffered snapshot.
+    /// Stepping forward resumes tracking the target time.
+    pub fn step_reverse(&mut self, delta_time: f64, buf: &impl SnapshotStore<T>) -> Option<T> {
+        let step = |play: &mut Self| {
+            let delta_time = play.clamp_delta_time(delta_time);
+
+            let start_playback_time = play.playback_time;
+            play.playback_time -= delta_time;
+            if let Some(oldest) = buf.back() {
+                play.playback_time = play
+                    .playback_time
+                    .max(buf.remote_time_of(oldest) - play.time_epoch);
             }
+            play.last_advance = play.playback_time - start_playback_time;
+            play.seed_advance = 0.0;
+            play.last_clamp = None;
+            play.last_skipped_count = 0;
+
+            let playback_time = play.sampling_time(play.playback_time);
+            let (snapshots, extrapolating) = Self::bracket(playback_time, play.time_epoch, buf);
+            (delta_time, snapshots, extrapolating)
+        };
+        let interpolate = |play: &Self, playback_time, snapshots| match snapshots {
+            Some(_) => Self::interpolate(
+                playback_time,
+                play.time_epoch,
+                snapshots,
+                buf,
+                |t, prev, from, to| play.blend_segment(t, prev, from, to),
+            ),
+            // Rewound to the oldest snapshot
+            None => buf.back().cloned(),
+        };
+        self.play(buf, step, interpolate).0
+    }
+
+    /// Step as with `step`, but only returning the output when it differs
+    /// from the output last returned by more than `epsilon`, according to
+    /// `Snapshot::approx_eq`. Avoids redundant downstream updates.
+    pub fn step_on_change(
+        &mut self,
+        delta_time: f64,
+        buf: &impl SnapshotStore<T>,
+        epsilon: f64,
+    ) -> Option<T> {
+        let output = self.step(delta_time, buf)?;
+        if self
+            .last_changed_output
+            .as_ref()
+            .is_some_and(|last| output.approx_eq(last, epsilon))
+        {
+            return None;
+        }
+
+        self.last_changed_output = Some(output.clone());
+        Some(output)
+    }
+
+    /// Step as with `step`, also returning the authoritative snapshots
+    /// interpolated between, exactly as selected internally. e.g. for
+    /// re-simulating between them in rollback netcode. When extrapolating
+    /// these are the newest two. `None` when there isn't a pair to
+    /// interpolate between. With `Settings::raw_passthrough`, the newest
+    /// snapshot as the newest two, or as itself alone should it be the
+    /// only one.
+    ///
+    /// Returns `(interpolated, from, to)`.
+    pub fn step_with_neighbors(
+        &mut self,
+        delta_time: f64,
+        buf: &impl SnapshotStore<T>,
+    ) -> Option<(T, T, T)> {
+        let (output, snapshots, _) = self.step_snapshots(delta_time, buf);
+        let (ss_from, ss_to) = snapshots?;
+        Some((output?, ss_from.clone(), ss_to.clone()))
+    }
+
+    /// Step, returning the output, the snapshots interpolated between, and
+    /// whether doing so extrapolated
+    fn step_snapshots<'a>(
+        &mut self,
+        delta_time: f64,
+        buf: &'a impl SnapshotStore<T>,
+    ) -> (Option<T>, Option<(&'a T, &'a T)>, bool) {
+        let step = |play: &mut Self| {
+            let delta_time = play.clamp_delta_time(delta_time);
+            let (snapshots, extrapolating) = play.advance(delta_time, buf);
+            (delta_time, snapshots, extrapolating)
+        };
+        let interpolate = |play: &Self, playback_time, snapshots| {
+            Self::interpolate(
+                playback_time,
+                play.time_epoch,
+                snapshots,
+                buf,
+                |t, prev, from, to| play.blend_segment(t, prev, from, to),
+            )
+        };
+        self.play(buf, step, interpolate)
+    }
+
+    /// The step shared by every step entry point. Moves the playback time
+    /// with `step`, which returns the (clamped) delta time stepped by, the
+    /// snapshots to interpolate between and whether that extrapolates,
+    /// then interpolates them at the sampling time with `interpolate`.
+    /// Returns the output, the snapshots and whether it extrapolated.
+    ///
+    /// With `Settings::raw_passthrough` the playback isn't moved, and the
+    /// newest snapshot is output as is.
+    fn play<'a, S: SnapshotStore<T>>(
+        &mut self,
+        buf: &'a S,
+        step: impl FnOnce(&mut Self) -> (f64, Option<(&'a T, &'a T)>, bool),
+        interpolate: impl FnOnce(&Self, f64, Option<(&'a T, &'a T)>) -> Option<T>,
+    ) -> (Option<T>, Option<(&'a T, &'a T)>, bool) {
+        if self.settings.raw_passthrough {
+            // Nothing to prime the playback with
+            self.seeded = true;
+            let newest = buf.front();
+            let snapshots = newest.map(|to| (buf.get(1).unwrap_or(to), to));
+            return (newest.cloned(), snapshots, false);
+        }
+
+        let (delta_time, snapshots, extrapolating) = step(self);
+
+        // 6. Interpolate
+        let playback_time = self.sampling_time(self.playback_time);
+        self.record_segment_progress(playback_time, snapshots, buf);
+        let output = self.interpolate_held(delta_time, |play| {
+            let output = interpolate(play, playback_time, snapshots);
+            play.apply_underrun(playback_time, snapshots, buf, output)
+        });
+        (
+            self.finish_step(delta_time, output),
+            snapshots,
+            extrapolating,
+        )
+    }
+
+    /// Step many independent playbacks, each over its respective store,
+    /// returning each output in order. The clock is read once for the
+    /// whole batch rather than once per playback, so that every playback
+    /// steps as of the same instant.
+    ///
+    /// Panics if `playbacks` and `bufs` differ in length.
+    pub fn step_many(
+        playbacks: &mut [&mut Playback<T>],
+        delta_time: f64,
+        bufs: &[&impl SnapshotStore<T>],
+    ) -> Vec<Option<T>> {
+        assert_eq!(playbacks.len(), bufs.len());
+
+        let now = Instant::now();
+        playbacks
+            .iter_mut()
+            .zip(bufs)
+            .map(|(playback, buf)| {
+                playback.step_now = Some(now);
+                let output = playback.step(delta_time, *buf);
+                playback.step_now = None;
+                output
+            })
+            .collect()
+    }
+
+    /// Step as with `step`, but interpolating within a caller supplied
+    /// reference frame (e.g. a moving platform the entity is attached to)
+    /// using `FrameSnapshot::interpolate_in_frame`. Any `interpolate_fn`
+    /// isn't used.
+    pub fn step_in_frame(
+        &mut self,
+        delta_time: f64,
+        buf: &impl SnapshotStore<T>,
+        frame: &T::Frame,
+    ) -> Option<T>
+    where
+        T: FrameSnapshot,
+    {
+        let step = |play: &mut Self| {
+            let delta_time = play.clamp_delta_time(delta_time);
+            let (snapshots, extrapolating) = play.advance(delta_time, buf);
+            (delta_time, snapshots, extrapolating)
         };
+        let interpolate = |play: &Self, playback_time, snapshots| {
+            Self::interpolate(
+                playback_time,
+                play.time_epoch,
+                snapshots,
+                buf,
+                |t, _, from, to| {
+                    let t = play.ease(t.min(play.max_extrapolation_t()));
+                    T::interpolate_in_frame(t, from, to, frame)
+                },
+            )
+        };
+        self.play(buf, step, interpolate).0
+    }
+
+    /// Guard against clock glitches (e.g. a clock going backwards) by
+    /// clamping the delta time between zero and `Settings::max_delta_time`.
+    fn clamp_delta_time(&mut self, delta_time: f64) -> f64 {
+        // Also catches NaN
+        if !(0.0..=self.settings.max_delta_time).contains(&delta_time) {
+            self.db_delta_clamping_ema.add(1.0);
+            if delta_time > self.settings.max_delta_time {
+                self.settings.max_delta_time
+            } else {
+                0.0
+            }
+        } else {
+            self.db_delta_clamping_ema.add(0.0);
+            delta_time
+        }
+    }
+
+    /// Steps 0 through 5 of `step`, advancing and correcting the playback
+    /// time. Returns the snapshots between which to interpolate, and
+    /// whether doing so extrapolates.
+    fn advance<'a>(
+        &mut self,
+        delta_time: f64,
+        buf: &'a impl SnapshotStore<T>,
+    ) -> (Option<(&'a T, &'a T)>, bool) {
+        let playback_clamp = self.settings.playback_clamp() as f64;
+
+        self.follow_time_epoch(buf);
+
+        // Zero delta times re-sample rather than render a new frame
+        if delta_time > 0.0 {
+            self.frame_time.add(delta_time);
+        }
+
+        let start_playback_time = self.playback_time;
+        self.last_clamp = None;
+        self.seed_advance = 0.0;
+
+        // 0. Seed the playback time from the primed buffer, rather than
+        // ramping up from zero, or from the first of a burst of arrivals
+        if !self.seeded {
+            let warmup_snapshots = self.settings.warmup_snapshots.max(1);
+            if buf.get(warmup_snapshots - 1).is_none() {
+                self.last_advance = 0.0;
+                self.last_skipped_count = 0;
+                return (None, true);
+            }
+
+            self.seeded = true;
+            self.playback_time = self.target_playback_time(buf);
+            self.seed_advance = self.playback_time - start_playback_time;
+        }
+
+        // With a single snapshot there's nothing to interpolate between,
+        // nor to measure the remote's timescale against, so hold the
+        // playback time at the target until a second snapshot arrives
+        let single = buf.front().is_some() && buf.get(1).is_none();
+
+        // 1. Step playback time
+        if single {
+            self.playback_time = self.target_playback_time(buf);
+        } else {
+            self.playback_time += delta_time * self.timescale;
+            self.converge_soft_clamp(delta_time);
+        }
+        self.timescale_dt += delta_time;
+        self.cap_extrapolation(buf);
+
+        // 2. Find the packets between which to interpolate (for later)
+        let bracketed_time = self.playback_time;
+        let (mut snapshots, mut extrapolating) =
+            Self::bracket(self.sampling_time(self.playback_time), self.time_epoch, buf);
+        if let Some((ss_from, ss_to)) = snapshots {
+            self.segment_period = buf.remote_time_of(ss_to) - buf.remote_time_of(ss_from);
+        }
 
         // A new network packet has arrived into the buffer
-        if self.remote_counter != buf.last_remote_counter {
-            self.remote_counter = buf.last_remote_counter;
+        if self.remote_counter != buf.arrival_counter() {
+            self.remote_counter = buf.arrival_counter();
 
             // 3. Clamp the playback time about the target time
-            let remote_time = buf.last_remote_time
-                // Account for any time which has passed since we, the local client, first
-                // saw this packet arrive in the buffer.
-                + buf.last_remote_instant.elapsed().as_secs_f64();
-            let playback_target_time = remote_time - playback_offset;
+            let playback_target_time = self.target_playback_time(buf);
             {
                 let min = playback_target_time - playback_clamp;
                 let max = playback_target_time + playback_clamp;
 
                 if self.playback_time < min {
-                    self.playback_time = min;
+                    self.last_clamp = Some(ClampEvent {
+                        bound: ClampBound::Min,
+                        amount: min - self.playback_time,
+                    });
+                    self.clamp_to(min);
                     self.db_clamping_ema.add(1.0);
                 } else if self.playback_time > max {
-                    self.playback_time = max;
+                    self.last_clamp = Some(ClampEvent {
+                        bound: ClampBound::Max,
+                        amount: self.playback_time - max,
+                    });
+                    self.clamp_to(max);
                     self.db_clamping_ema.add(1.0);
                 } else {
                     self.db_clamping_ema.add(0.0);
                 }
             };
-            self.db_extrapolating_ema.add(extrapolating);
+            self.db_extrapolating_ema
+                .add(if extrapolating && !single { 1.0 } else { 0.0 });
+            self.cap_extrapolation(buf);
+
+            if self.settings.disable_timescale {
+                // Advance at exactly real time
+                self.timescale = 1.0;
+                self.target_timescale = 1.0;
+                self.timescale_dt = 0.0;
+            } else {
+                // 4. Add catchup time to moving average
+                let catchup_time = playback_target_time - self.playback_time;
+                self.catchup_time.add(catchup_time);
+
+                // Trust the catchup less the longer since the previous arrival
+                let late_periods = (self.timescale_dt / self.settings.period - 1.0).max(0.0);
+                self.catchup_confidence.add((-late_periods).exp());
+
+                // 5. Compute the timescale in order to best track the remote's timescale
+                let mut catchup_time = self.catchup_time.value.unwrap_or(0.0);
+                if self.settings.catchup_loss_confidence {
+                    catchup_time *= self.catchup_confidence.value.unwrap_or(1.0);
+                }
+                self.target_timescale = match &mut self.timescale_controller {
+                    Some(controller) => {
+                        let timescale = controller.compute(catchup_time, self.timescale_dt);
+                        self.constrain_timescale(timescale)
+                    }
+                    None => {
+                        let timescale = self.timescale(catchup_time);
+                        self.debounce_timescale(timescale)
+                    }
+                };
+                self.db_scaling_ema.add(if self.target_timescale != 1.0 {
+                    1.0
+                } else {
+                    0.0
+                });
+                self.timescale_dt = 0.0;
 
-            // 4. Add catchup time to moving average
-            let catchup_time = playback_target_time - self.playback_time;
-            self.catchup_time.add(catchup_time);
+                if self.settings.timescale_slew_per_second.is_none() {
+                    self.timescale = self.target_timescale;
+                }
+            }
+        }
 
-            // 5. Compute the timescale in order to best track the remote's timescale
-            self.timescale = self.timescale(self.catchup_time.value.unwrap_or(0.0));
+        // The clamp may have moved the playback time onto another segment,
+        // which must be interpolated rather than the one bracketed before
+        if self.playback_time != bracketed_time {
+            (snapshots, extrapolating) =
+                Self::bracket(self.sampling_time(self.playback_time), self.time_epoch, buf);
         }
 
-        // 6. Interpolate
+        // Ramp the timescale toward the target speed
+        if let Some(slew_per_second) = self.settings.timescale_slew_per_second {
+            let max_change = slew_per_second * delta_time;
+            self.timescale +=
+                (self.target_timescale - self.timescale).clamp(-max_change, max_change);
+        }
+
+        self.last_advance = self.playback_time - start_playback_time;
+        self.last_skipped_count = self.count_skipped(start_playback_time, buf);
+
+        // Measure how long the snapshot the playback time just reached dwelt in the buffer
+        if let Some(played_pos) = buf
+            .iter()
+            .position(|b| buf.remote_time_of(b) - self.time_epoch < self.playback_time)
+            && let Some(played) = buf.get(played_pos)
+            && self
+                .last_played_time
+                .is_none_or(|last_played_time| buf.remote_time_of(played) > last_played_time)
+        {
+            self.last_played_time = Some(buf.remote_time_of(played));
+            if let Some(inserted) = buf.inserted_at(played_pos) {
+                let now = self.step_now.unwrap_or_else(Instant::now);
+                let dwell_time = now.saturating_duration_since(inserted);
+                self.dwell_time.add(dwell_time.as_secs_f64());
+            }
+        }
+
+        (snapshots, extrapolating)
+    }
+
+    /// Move the playback time onto a clamp `bound`, immediately, or over
+    /// a soft clamp's duration
+    fn clamp_to(&mut self, bound: f64) {
+        match self.settings.soft_clamp() {
+            Some(duration) if duration > 0.0 => {
+                let distance = bound - self.playback_time;
+                self.soft_clamp = Some((distance, distance.abs() / duration as f64));
+            }
+            _ => self.playback_time = bound,
+        }
+    }
+
+    /// Step the playback time toward any soft clamp's bound, at most
+    /// holding the playback time rather than stepping it backward
+    fn converge_soft_clamp(&mut self, delta_time: f64) {
+        let Some((distance, rate)) = &mut self.soft_clamp else {
+            return;
+        };
+
+        let step = (*rate * delta_time).min(distance.abs()).copysign(*distance);
+        let step = step.max(-delta_time * self.timescale);
+        self.playback_time += step;
+        *distance -= step;
+        if *distance == 0.0 {
+            self.soft_clamp = None;
+        }
+    }
+
+    /// Follow any re-basing of the store's epoch
+    fn follow_time_epoch(&mut self, buf: &impl SnapshotStore<T>) {
+        let time_epoch = buf.time_epoch();
+        if time_epoch != self.time_epoch {
+            self.playback_time += self.time_epoch - time_epoch;
+            self.time_epoch = time_epoch;
+        }
+    }
+
+    /// Count the snapshots which the playback time passed since
+    /// `start_playback_time` without playing, i.e. all but the newest
+    /// passed, which the playback is now interpolating onward from
+    fn count_skipped(&self, start_playback_time: f64, buf: &impl SnapshotStore<T>) -> usize {
+        let passed = buf
+            .iter()
+            .map(|s| buf.remote_time_of(s) - self.time_epoch)
+            .filter(|&time| start_playback_time < time && time <= self.playback_time)
+            .count();
+        passed.saturating_sub(1)
+    }
+
+    /// Never let the playback time run beyond
+    /// `Settings::max_extrapolation_periods` past the newest snapshot, or
+    /// that of any extrapolation override
+    fn cap_extrapolation(&mut self, buf: &impl SnapshotStore<T>) {
+        if let Some(cap) = self.extrapolation_cap(buf) {
+            self.playback_time = self.playback_time.min(cap);
+        }
+    }
+
+    /// The playback time beyond which `cap_extrapolation` never lets the
+    /// playback run, if any
+    fn extrapolation_cap(&self, buf: &impl SnapshotStore<T>) -> Option<f64> {
+        let max_periods = self.extrapolation().max_periods?;
+        let newest = buf.front()?;
+        let max_extrapolation = (self.settings.period as f32 * max_periods) as f64;
+        Some(buf.remote_time_of(newest) - self.time_epoch + max_extrapolation)
+    }
+
+    /// Apply the `Underrun` policy in effect to the `output` interpolated
+    /// between `snapshots` at the playback time, should the playback be
+    /// starved beyond its extrapolation policy
+    fn apply_underrun(
+        &self,
+        playback_time: f64,
+        snapshots: Option<(&T, &T)>,
+        buf: &impl SnapshotStore<T>,
+        output: Option<T>,
+    ) -> Option<T> {
+        if self.extrapolation().underrun == Underrun::Hold {
+            return output;
+        }
+        let Some((ss_from, ss_to)) = snapshots else {
+            return output;
+        };
+
+        let t = linear_map(
+            playback_time,
+            buf.remote_time_of(ss_from) - self.time_epoch,
+            buf.remote_time_of(ss_to) - self.time_epoch,
+            0.0,
+            1.0,
+        );
+        let capped = self
+            .extrapolation_cap(buf)
+            .is_some_and(|cap| self.playback_time >= cap);
+        let underrun = t > 1.0 && (t >= self.max_extrapolation_t() || capped);
+        output.filter(|_| !underrun)
+    }
+
+    /// Record the progress through the segment being played, firing the
+    /// progress callback for any thresholds crossed since the last step,
+    /// including those remaining in a segment just left and those of any
+    /// segments passed through entirely within the step.
+    fn record_segment_progress(
+        &mut self,
+        playback_time: f64,
+        snapshots: Option<(&T, &T)>,
+        buf: &impl SnapshotStore<T>,
+    ) {
+        let Some((ss_from, ss_to)) = snapshots else {
+            self.last_segment_progress = None;
+            return;
+        };
+        let Some(index) = buf.iter().position(|s| std::ptr::eq(s, ss_from)) else {
+            return;
+        };
+
+        let t = linear_map(
+            playback_time,
+            buf.remote_time_of(ss_from) - self.time_epoch,
+            buf.remote_time_of(ss_to) - self.time_epoch,
+            0.0,
+            1.0,
+        )
+        .clamp(0.0, MAX_EXTRAPOLATION_T);
+
+        let from_time = buf.remote_time_of(ss_from);
+        let previous = self.last_segment_progress.replace((index, t));
+        let last_from_time = std::mem::replace(&mut self.last_segment_from_time, from_time);
+
+        let Some((thresholds, callback)) = &mut self.progress_callback else {
+            return;
+        };
+        let mut fire = |index: usize, start: f64, end: f64| {
+            for &threshold in thresholds.iter() {
+                if start < threshold && threshold <= end {
+                    callback(index, threshold);
+                }
+            }
+        };
+
+        // Nothing is crossed on first reaching a segment other than by
+        // moving forward from the last
+        match previous {
+            Some((_, last_t)) if from_time == last_from_time => fire(index, last_t, t),
+            Some((_, last_t)) if from_time > last_from_time => {
+                if let Some(last_index) = buf
+                    .iter()
+                    .position(|s| buf.remote_time_of(s) == last_from_time)
+                {
+                    fire(last_index, last_t, 1.0);
+                }
+
+                // Those between, just behind the segment being played as the
+                // store is newest first. Oldest first, as they were played.
+                let passed = buf
+                    .iter()
+                    .skip(index + 1)
+                    .take_while(|s| buf.remote_time_of(s) > last_from_time)
+                    .count();
+                for position in (index + 1..=index + passed).rev() {
+                    fire(position, 0.0, 1.0);
+                }
+
+                fire(index, 0.0, t);
+            }
+            _ => {}
+        }
+    }
+
+    /// Interpolate with `interpolate`, unless it's too soon to interpolate
+    /// again per `Settings::max_interp_hz`, in which case the last
+    /// interpolation is held instead
+    fn interpolate_held(
+        &mut self,
+        delta_time: f64,
+        interpolate: impl FnOnce(&Self) -> Option<T>,
+    ) -> Option<T> {
+        if let Some(held) = self.hold_interpolation(delta_time) {
+            return held;
+        }
+
+        let output = interpolate(self);
+        if self.settings.max_interp_hz.is_some() {
+            let elapsed = self.held_interpolation.as_ref().map_or(0.0, |(_, e)| *e);
+            self.held_interpolation = Some((output.clone(), elapsed));
+        }
+        output
+    }
+
+    /// The held interpolation, should it be too soon to interpolate again
+    /// per `Settings::max_interp_hz`
+    fn hold_interpolation(&mut self, delta_time: f64) -> Option<Option<T>> {
+        let interval = 1.0 / self.settings.max_interp_hz?;
+        let (held, elapsed) = self.held_interpolation.as_mut()?;
+        *elapsed += delta_time;
+
+        // Interpolate on whichever step lands nearest the interval, so
+        // that e.g. 120Hz steps capped to 30Hz interpolate every 4th step
+        if *elapsed + delta_time / 2.0 < interval {
+            return Some(held.clone());
+        }
+        // Keep the remainder, so interpolations keep to the rate on average
+        *elapsed = (*elapsed - interval).clamp(0.0, interval);
+        None
+    }
+
+    fn finish_step(&mut self, delta_time: f64, output: Option<T>) -> Option<T> {
+        // Nothing is played back until the buffer is primed
+        let output = output.filter(|_| self.seeded);
+        let mut output = self.blend_handover(delta_time, output);
+
+        if let Some((prototype, filters)) = &mut self.one_euro_filter
+            && let Some(output) = &mut output
+        {
+            let mut index = 0;
+            output.filter_values(&mut |value| {
+                if filters.len() <= index {
+                    filters.push(prototype.clone());
+                }
+                *value = filters[index].filter(*value, delta_time);
+                index += 1;
+            });
+        }
+
+        if let Some(step) = self.settings.output_quantize
+            && let Some(output) = &mut output
+        {
+            output.quantize(step);
+        }
+
+        self.last_output.clone_from(&output);
+        output
+    }
+
+    /// The playback time which the playback aims for, the remote's
+    /// estimated current time less the playback offset, measured from
+    /// `time_epoch`. (seconds)
+    pub fn target_playback_time(&self, buf: &impl SnapshotStore<T>) -> f64 {
+        self.target_playback_time_at(self.step_now, buf)
+    }
+
+    /// `target_playback_time` as of the local instant `now`, otherwise the
+    /// current instant, e.g. for a simulated clock (seconds)
+    pub(crate) fn target_playback_time_at(
+        &self,
+        now: Option<Instant>,
+        buf: &impl SnapshotStore<T>,
+    ) -> f64 {
+        // Snapshots are only seen once per step, so on average they arrived
+        // half a frame before the step which first plays them. Without this
+        // slow framerates would settle further behind than fast ones.
+        let frame_time = self.frame_time.value.unwrap_or(0.0) / 2.0;
+
+        // Account for any time which has passed since we, the local client, first
+        // saw the latest packet arrive in the buffer.
+        let remote_now = match now {
+            Some(now) => buf.estimated_remote_now_at(now),
+            None => buf.estimated_remote_now(),
+        };
+        remote_now - self.time_epoch - buf.playback_offset(self.settings) + frame_time
+    }
+
+    /// The epoch from which `playback_time`, and the other playback
+    /// times, are measured. Zero unless the remote times are large, in
+    /// which case it's periodically re-based to keep them precise.
+    /// (seconds)
+    pub fn time_epoch(&self) -> f64 {
+        self.time_epoch
+    }
+
+    /// The worst case delay between the remote producing a state and its
+    /// presentation (seconds): the playback offset, including the dynamic
+    /// jitter term, plus the clamp allowance the playback may lag behind
+    /// the target, plus a period for the age of the newest snapshot, less
+    /// any lookahead. Typical latency is the playback offset alone.
+    pub fn max_presentation_latency(&self, buf: &impl SnapshotStore<T>) -> f64 {
+        buf.playback_offset(self.settings)
+            + self.settings.playback_clamp() as f64
+            + self.settings.period
+            - self.settings.lookahead() as f64
+    }
+
+    /// The raw error signal between the target playback time and the
+    /// actual playback time, unsmoothed by the `catchup_time` moving
+    /// average. Positive when the playback is behind. (seconds)
+    pub fn instantaneous_error(&self, buf: &impl SnapshotStore<T>) -> f64 {
+        self.target_playback_time(buf) - self.playback_time
+    }
+
+    /// Switch to playing back a different stream (e.g. spectating another
+    /// entity), blending from the last output of the previous stream toward
+    /// the new stream over `blend_periods` periods, rather than jumping.
+    ///
+    /// Subsequent steps must be given `new_buf`.
+    pub fn handover(&mut self, new_buf: &impl SnapshotStore<T>, blend_periods: f32) {
+        // Resync with the new stream's timeline
+        self.remote_counter = new_buf.arrival_counter();
+        self.time_epoch = new_buf.time_epoch();
+        self.playback_time = self.target_playback_time(new_buf);
+        self.seeded = true;
+        self.last_played_time = None;
+        self.timescale = 1.0;
+        self.target_timescale = 1.0;
+        self.soft_clamp = None;
+        self.timescale_held = f64::INFINITY;
+        self.catchup_time.reset();
+
+        self.handover = self.last_output.take().map(|from| Handover {
+            from,
+            duration: self.settings.period * blend_periods as f64,
+            elapsed: 0.0,
+        });
+    }
+
+    /// Ease in from `origin` (e.g. a spawn point) toward the interpolated
+    /// output over `duration_periods` periods on subsequent steps, rather
+    /// than popping into place. `origin` is held until there's output to
+    /// ease toward.
+    pub fn ease_in_from(&mut self, origin: T, duration_periods: f32) {
+        self.handover = Some(Handover {
+            from: origin,
+            duration: self.settings.period * duration_periods as f64,
+            elapsed: 0.0,
+        });
+    }
+
+    fn blend_handover(&mut self, delta_time: f64, output: Option<T>) -> Option<T> {
+        let Some(handover) = &mut self.handover else {
+            return output;
+        };
+
+        // Don't count down the blend while there's nothing to blend toward
+        if output.is_some() {
+            handover.elapsed += delta_time;
+        }
+        let t = handover.elapsed / handover.duration;
+        if t >= 1.0 || !t.is_finite() {
+            self.handover = None;
+            return output;
+        }
+
+        let handover = self.handover.as_ref()?;
+        match output {
+            Some(output) => Some(self.blend(t, &handover.from, &output)),
+            None => Some(handover.from.clone()),
+        }
+    }
+
+    /// Interpolate the buffer at an arbitrary playback time (seconds,
+    /// measured from `time_epoch`), without stepping or otherwise mutating
+    /// the playback. Useful for scrubbing.
+    pub fn sample(&self, playback_time: f64, buf: &impl SnapshotStore<T>) -> Option<T> {
+        let (snapshots, _) = Self::bracket(playback_time, self.time_epoch, buf);
+        Self::interpolate(
+            playback_time,
+            self.time_epoch,
+            snapshots,
+            buf,
+            |t, prev, from, to| self.blend_segment(t, prev, from, to),
+        )
+    }
+
+    /// Interpolate `seconds_before_now` (in remote time) before where the
+    /// last step interpolated, without stepping or otherwise mutating the
+    /// playback, e.g. gathering sub-frame samples across a shutter interval
+    /// for motion blur. Zero samples exactly where the last step did, less
+    /// any handover or filtering applied to its output.
+    pub fn sample_offset(&self, seconds_before_now: f64, buf: &impl SnapshotStore<T>) -> Option<T> {
+        let playback_time = self.sampling_time(self.playback_time);
+        self.sample(playback_time - seconds_before_now, buf)
+    }
+
+    /// Whether stepping by `delta_time` (seconds) would extrapolate, i.e.
+    /// there isn't a snapshot in the buffer beyond the prospective playback
+    /// time. Doesn't mutate the playback.
+    pub fn would_extrapolate(&self, delta_time: f64, buf: &impl SnapshotStore<T>) -> bool {
+        let playback_time = self.sampling_time(self.playback_time + delta_time * self.timescale);
+        Self::bracket(playback_time, self.time_epoch, buf).1
+    }
+
+    /// The time at which the buffer is sampled at a playback time, i.e.
+    /// ahead of it by any `Settings::lookahead_periods` (seconds, measured
+    /// from `time_epoch`)
+    fn sampling_time(&self, playback_time: f64) -> f64 {
+        playback_time + self.settings.lookahead() as f64
+    }
+
+    /// Find the snapshots between which to interpolate at the playback
+    /// time, and whether doing so requires extrapolating.
+    fn bracket(
+        playback_time: f64,
+        time_epoch: f64,
+        buf: &impl SnapshotStore<T>,
+    ) -> (Option<(&T, &T)>, bool) {
+        let (positions, extrapolating) = bracket_positions(playback_time, time_epoch, buf);
+        let snapshots =
+            positions.and_then(|(from_pos, to_pos)| buf.get(from_pos).zip(buf.get(to_pos)));
+        (snapshots, extrapolating)
+    }
+
+    fn interpolate(
+        playback_time: f64,
+        time_epoch: f64,
+        snapshots: Option<(&T, &T)>,
+        buf: &impl SnapshotStore<T>,
+        blend: impl Fn(f64, Option<&T>, &T, &T) -> T,
+    ) -> Option<T> {
+        // The snapshot's entity has ceased to exist by the playback time
+        let expired = |s: &T| {
+            s.valid_until()
+                .is_some_and(|valid_until| playback_time > valid_until - time_epoch)
+        };
+
         if let Some((ss_from, ss_to)) = snapshots {
+            if expired(ss_from) {
+                return None;
+            }
+
+            if !can_blend(ss_from, ss_to) {
+                return Some(ss_to.clone());
+            }
+
             let t = linear_map(
-                self.playback_time,
-                ss_from.remote_time(),
-                ss_to.remote_time(),
+                playback_time,
+                buf.remote_time_of(ss_from) - time_epoch,
+                buf.remote_time_of(ss_to) - time_epoch,
                 0.0,
                 1.0,
             );
+            let mut output = if t > 1.0 && !T::EXTRAPOLATABLE {
+                // Hold the newest snapshot rather than extrapolate it
+                ss_to.clone()
+            } else {
+                // The snapshot preceding the segment, for a curve through it
+                let ss_prev = buf
+                    .iter()
+                    .find(|s| buf.remote_time_of(s) < buf.remote_time_of(ss_from))
+                    .filter(|s| can_blend(*s, ss_from));
 
-            Some(Snapshot::interpolate(t.clamp(0.0, 2.5), ss_from, ss_to))
+                blend(t.clamp(0.0, MAX_EXTRAPOLATION_T), ss_prev, ss_from, ss_to)
+            };
+            output.clamp();
+            Some(output)
         } else {
             // There isn't any packet in the buffer which arrived before the playback time
 
-            buf.latest().cloned()
+            buf.front().filter(|s| !expired(s)).cloned()
+        }
+    }
+
+    fn blend(&self, t: f64, from: &T, to: &T) -> T {
+        match &self.interpolate_fn {
+            Some(interpolate_fn) => interpolate_fn(t, from, to),
+            None => Snapshot::interpolate(t, from, to),
+        }
+    }
+
+    /// Blend across the segment being played, through the preceding
+    /// snapshot with `Snapshot::interpolate_quadratic` where there is one,
+    /// or beyond it with `Snapshot::extrapolate`. Any `interpolate_fn` is
+    /// used as is.
+    fn blend_segment(&self, t: f64, prev: Option<&T>, from: &T, to: &T) -> T {
+        let t = self.ease(t.min(self.max_extrapolation_t()));
+        let t = if self.arc_length_reparam && (0.0..=1.0).contains(&t) {
+            Self::reparam_arc_length(t, |t| self.blend_curve(t, prev, from, to))
+        } else {
+            t
+        };
+        self.blend_curve(t, prev, from, to)
+    }
+
+    /// The curve through the segment which `blend_segment` follows
+    fn blend_curve(&self, t: f64, prev: Option<&T>, from: &T, to: &T) -> T {
+        match prev {
+            _ if self.interpolate_fn.is_some() => self.blend(t, from, to),
+            _ if t > 1.0 => Snapshot::extrapolate(from, to, t, self.extrapolation().decay),
+            Some(prev) => Snapshot::interpolate_quadratic(t, prev, from, to),
+            None => Snapshot::interpolate(t, from, to),
+        }
+    }
+
+    /// Find the parameter along `curve` at which `fraction` of its arc
+    /// length has been travelled, measured between evenly spaced samples.
+    fn reparam_arc_length(fraction: f64, curve: impl Fn(f64) -> T) -> f64 {
+        const SAMPLES: usize = 16;
+
+        let mut lengths = [0.0; SAMPLES + 1];
+        let mut last = curve(0.0);
+        for (i, length) in lengths.iter_mut().enumerate().skip(1) {
+            let sample = curve(i as f64 / SAMPLES as f64);
+            *length = last.arc_length(&sample);
+            last = sample;
+        }
+        let total: f64 = lengths.iter().sum();
+        if total <= 0.0 {
+            return fraction;
+        }
+
+        let mut target = fraction * total;
+        for (i, &length) in lengths.iter().enumerate().skip(1) {
+            if target <= length {
+                let within = if length > 0.0 { target / length } else { 0.0 };
+                return (i as f64 - 1.0 + within) / SAMPLES as f64;
+            }
+            target -= length;
+        }
+        1.0
+    }
+
+    /// Apply any easing curve to an interpolation parameter. Extrapolation
+    /// is left linear.
+    fn ease(&self, t: f64) -> f64 {
+        match &self.easing_curve {
+            Some(easing_curve) if t <= 1.0 => easing_curve.sample(t),
+            _ => t,
         }
     }
 
-    pub fn timescale(&mut self, catchup_time: f64) -> f64 {
-        if catchup_time < self.settings.slow_threshold() as f64 {
-            self.db_scaling_ema.add(1.0);
+    /// The net change in `playback_time` during the most recent `step`,
+    /// including any clamping. This is the effective remote time which
+    /// elapsed for the remote entity, as opposed to `delta_time`.
+    /// (seconds)
+    pub fn last_advance(&self) -> f64 {
+        self.last_advance
+    }
+
+    /// The remote time which the most recent `step` played through, for
+    /// advancing time driven systems (e.g. an animation clock, so that
+    /// footsteps line up with movement) in lockstep with the playback.
+    /// Accounts for the timescale and clamps, as with `last_advance`, but
+    /// excludes the jump seeding the playback time, so summing it over
+    /// steps gives the remote time traversed. Negative when a clamp or
+    /// `step_reverse` steps backward. (seconds)
+    pub fn effective_remote_dt(&self) -> f64 {
+        self.last_advance - self.seed_advance
+    }
+
+    /// The index in the store of the snapshot starting the segment played
+    /// during the most recent step, and the interpolation parameter
+    /// through it (beyond 1.0 when extrapolating). The index shifts as
+    /// newer snapshots arrive. `None` when there wasn't a segment.
+    pub fn last_segment_progress(&self) -> Option<(usize, f64)> {
+        self.last_segment_progress
+    }
+
+    /// How many buffered snapshots the playback time passed over entirely
+    /// during the most recent step, never having been interpolated from,
+    /// e.g. due to a long frame or a clamp. Frequent skips suggest the
+    /// frame rate or clamping is eating data.
+    pub fn last_skipped_count(&self) -> usize {
+        self.last_skipped_count
+    }
+
+    /// The clamp applied to the playback time during the most recent
+    /// `step`, if any. Distinguishes snapping forward from too far behind
+    /// the target time, from snapping back from too far ahead.
+    pub fn last_clamp(&self) -> Option<ClampEvent> {
+        self.last_clamp
+    }
+
+    /// A single score of how cleanly the playback has been interpolating
+    /// over the last 10 seconds, between 1.0 - clean interpolation, and
+    /// 0.0 - degraded. Combines the debug EMAs weighted by
+    /// `Settings::quality_weights`.
+    pub fn last_quality(&self) -> f64 {
+        let weights = &self.settings.quality_weights;
+        let degradation = weights.extrapolating * self.db_extrapolating_ema.value.unwrap_or(0.0)
+            + weights.clamping * self.db_clamping_ema.value.unwrap_or(0.0)
+            + weights.scaling * self.db_scaling_ema.value.unwrap_or(0.0);
+
+        (1.0 - degradation).clamp(0.0, 1.0)
+    }
+
+    /// The state of the whole pipeline formatted as a table, one measure
+    /// per line, e.g. for a netgraph overlay. The debug EMAs are scaled
+    /// from 0 - good, to 10 - bad.
+    pub fn debug_report<A: Allocator>(&self, buf: &Buffer<T, A>) -> String {
+        let ms = |seconds: Option<f64>| match seconds {
+            Some(seconds) => format!("{:.1}ms", seconds * 1000.0),
+            None => "-".to_string(),
+        };
+        let score = |ema: &ExponentialMovingAverage| match ema.value {
+            Some(value) => format!("{:.0} (GOOD 0 - 10 BAD)", value * 10.0),
+            None => "-".to_string(),
+        };
+
+        let mut report = String::new();
+        let mut row = |name: &str, value: String| {
+            // Writing to a String never fails
+            let _ = writeln!(report, "{name:<18}{value}");
+        };
+        row(
+            "playback time",
+            format!("{:.3}s", self.time_epoch + self.playback_time),
+        );
+        row("timescale", format!("{:.3}", self.timescale));
+        row("catchup time", ms(self.catchup_time.value));
+        row("drift", ms(Some(self.instantaneous_error(buf))));
+        row("playback offset", ms(Some(buf.dynamic_playback_offset())));
+        row("remote delta time", ms(buf.remote_delta_time.value));
+        row("jitter", ms(Some(buf.remote_delta_time.std_dev)));
+        row("buffered", format!("{}/{}", buf.buf.len(), buf.buf_len));
+        row("dwell time", ms(self.dwell_time.value));
+        row("frame time", ms(self.frame_time.value));
+        row("dbg_extrapolating", score(&self.db_extrapolating_ema));
+        row("dbg_clamping", score(&self.db_clamping_ema));
+        row("dbg_scaling", score(&self.db_scaling_ema));
+        row("dbg_delta_clamp", score(&self.db_delta_clamping_ema));
+        row("quality", format!("{:.2}", self.last_quality()));
+        report
+    }
+
+    /// Begin slowing the playback when the catchup time is below this
+    /// threshold. Scaled by the measured period of the snapshots last
+    /// interpolated between. (seconds)
+    pub fn slow_threshold(&self) -> f64 {
+        self.segment_period * self.settings.playback_slow_periods as f64
+    }
+
+    /// Begin hastening the playback when the catchup time is above this
+    /// threshold. Scaled by the measured period of the snapshots last
+    /// interpolated between. (seconds)
+    pub fn fast_threshold(&self) -> f64 {
+        self.segment_period * self.settings.playback_fast_periods as f64
+    }
+
+    /// Never scale the timescale while the catchup time is within this
+    /// distance of zero. Scaled by the measured period of the snapshots
+    /// last interpolated between. (seconds)
+    pub fn dead_zone(&self) -> f64 {
+        self.segment_period * self.settings.catchup_dead_zone_periods as f64
+    }
+
+    /// How long the timescale holds after changing before it may change
+    /// again. Scaled by the measured period of the snapshots last
+    /// interpolated between. (seconds)
+    pub fn min_scaling_hold(&self) -> Option<f64> {
+        self.settings
+            .min_scaling_hold_periods
+            .map(|periods| self.segment_period * periods as f64)
+    }
+
+    pub fn timescale(&self, catchup_time: f64) -> f64 {
+        if catchup_time.abs() <= self.dead_zone() {
+            return 1.0;
+        }
+
+        let direction = self.settings.correction_direction;
+
+        if catchup_time < self.slow_threshold() && direction != CorrectionDirection::FastOnly {
             return self.settings.playback_slow_speed as f64;
         }
 
-        if catchup_time > self.settings.fast_threshold() as f64 {
-            self.db_scaling_ema.add(1.0);
+        if catchup_time > self.fast_threshold() && direction != CorrectionDirection::SlowOnly {
             return self.settings.playback_fast_speed as f64;
         }
 
-        self.db_scaling_ema.add(0.0);
         1.0
     }
+
+    /// Only change the target timescale to `timescale` once the current
+    /// target has held for `min_scaling_hold` since it last changed,
+    /// keeping it meanwhile
+    fn debounce_timescale(&mut self, timescale: f64) -> f64 {
+        let Some(hold) = self.min_scaling_hold() else {
+            return timescale;
+        };
+        self.timescale_held += self.timescale_dt;
+        if timescale == self.target_timescale || self.timescale_held < hold {
+            return self.target_timescale;
+        }
+
+        self.timescale_held = 0.0;
+        timescale
+    }
+
+    /// Restrict a timescale to `Settings::correction_direction`
+    fn constrain_timescale(&self, timescale: f64) -> f64 {
+        match self.settings.correction_direction {
+            CorrectionDirection::Both => timescale,
+            CorrectionDirection::SlowOnly => timescale.min(1.0),
+            CorrectionDirection::FastOnly => timescale.max(1.0),
+        }
+    }
 }
@@ -1,10 +1,32 @@
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+
+mod allocator;
+mod controller;
+mod easing;
 mod ema;
+mod filter;
+mod group;
 mod interpolate;
 mod settings;
+mod smoothed;
 mod snapshot;
+mod spring;
+mod state;
+mod store;
 mod test;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
+pub use allocator::*;
+pub use controller::*;
+pub use easing::*;
 pub use ema::*;
+pub use filter::*;
+pub use group::*;
 pub use interpolate::*;
 pub use settings::*;
+pub use smoothed::*;
 pub use snapshot::*;
+pub use spring::*;
+pub use state::*;
+pub use store::*;
@@ -1,4 +1,76 @@
-use std::sync::LazyLock;
+use std::{fmt, sync::LazyLock};
+
+/// What to do when a snapshot arrives with the same remote time as a
+/// snapshot already in the buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeCollision {
+    /// Skip the new snapshot
+    KeepExisting,
+
+    /// Replace the existing snapshot, e.g. with a corrected re-send
+    ReplaceWithNew,
+}
+
+/// In which directions the playback may scale its timescale in order to
+/// track the target time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CorrectionDirection {
+    /// Both slow and hasten the playback
+    Both,
+
+    /// Never run faster than real time (e.g. audio-synced visuals),
+    /// relying on the clamp to catch up when behind
+    SlowOnly,
+
+    /// Never run slower than real time, relying on the clamp to fall
+    /// back when ahead
+    FastOnly,
+}
+
+/// How heavily each debug measure degrades `Playback::last_quality`.
+/// The quality is `1.0` less the weighted sum of the debug EMAs, so
+/// weights summing to `1.0` span the full range.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QualityWeights {
+    /// Weight of `db_extrapolating_ema`, the most visible degradation
+    pub extrapolating: f64,
+
+    /// Weight of `db_clamping_ema`, which causes a visible jump
+    pub clamping: f64,
+
+    /// Weight of `db_scaling_ema`, some of which is expected
+    pub scaling: f64,
+}
+
+impl Default for QualityWeights {
+    fn default() -> Self {
+        QualityWeights {
+            extrapolating: 0.6,
+            clamping: 0.3,
+            scaling: 0.1,
+        }
+    }
+}
+
+/// A latency budget too tight to interpolate within safely, see
+/// `Settings::for_latency_budget`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LatencyBudgetError {
+    /// The smallest budget which fits the period (milliseconds)
+    pub minimum_ms: f64,
+}
+
+impl fmt::Display for LatencyBudgetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "latency budget too tight for the period, at least {}ms is required",
+            self.minimum_ms
+        )
+    }
+}
+
+impl std::error::Error for LatencyBudgetError {}
 
 #[derive(Clone)]
 pub struct Settings {
@@ -6,6 +78,31 @@ pub struct Settings {
     /// buffer
     pub buf_duration: f32,
 
+    /// Size the buffer to the dynamic playback offset plus
+    /// `auto_buffer_margin_periods` on every insert, in place of
+    /// `buf_duration`, so that it holds no more than needed under the
+    /// measured jitter. Growing the buffer may allocate.
+    pub auto_buffer: bool,
+    pub auto_buffer_margin_periods: f32,
+
+    /// Don't begin playback until the buffer holds this many snapshots,
+    /// then begin the playback offset behind the newest. The buffer's
+    /// jitter measurements accumulate as usual meanwhile. Zero or one
+    /// begins with the first snapshot.
+    pub warmup_snapshots: usize,
+
+    /// Additionally evict the oldest snapshots while the buffer's
+    /// `Snapshot::approx_size` total exceeds this many bytes. The newest
+    /// snapshot is always kept.
+    pub max_buffer_bytes: Option<usize>,
+
+    /// The most snapshots the buffer may hold, however many
+    /// `buf_duration` (or `auto_buffer`) calls for. Guards against a tiny
+    /// `period` with a long `buf_duration` creating a pathologically large
+    /// buffer, which is allocated up front and scanned on every insert.
+    /// See `buf_len`.
+    pub max_buf_len: usize,
+
     /// The time period (seconds) between the server sending
     /// any two snapshots.
     pub period: f64,
@@ -22,55 +119,262 @@ pub struct Settings {
     /// last 2 seconds of received packets.
     pub dynamic_playback_jitter_duration: f32,
 
+    /// Periodically resize the window from which network jitter is
+    /// measured using the measured send rate, rather than the configured
+    /// `period`. Useful when the remote's real send rate may differ.
+    pub adaptive_ema_window: bool,
+
+    /// Exclude any time between packets more than this many standard
+    /// deviations from the mean from the jitter measurement, so that a
+    /// single outlier doesn't inflate the dynamic playback offset. A few
+    /// consecutive outliers are taken as a real change in network
+    /// conditions and measured as usual.
+    pub jitter_spike_reset: Option<f32>,
+
+    /// While the dynamic playback offset is frozen, see
+    /// `Buffer::freeze_offset`, unfreeze it should the measured jitter
+    /// (standard deviation) stray this many periods from when it was
+    /// frozen. `None` stays frozen until `Buffer::unfreeze_offset`.
+    pub offset_unfreeze_periods: Option<f32>,
+
+    /// Round the dynamic playback offset to the nearest multiple of this
+    /// many milliseconds, so that it changes in discrete steps with large
+    /// changes in jitter, rather than shifting the sampling point slightly
+    /// with every packet.
+    pub offset_quantum_ms: Option<f32>,
+
+    /// How many of the latest raw times between packets to keep, see
+    /// `Buffer::recent_deltas`
+    pub recent_deltas_len: usize,
+
+    /// What to do when a snapshot arrives with the same remote time as
+    /// one already in the buffer.
+    pub on_time_collision: TimeCollision,
+
     /// How far behind should the playback be? In multiples of the period
     pub playback_offset_periods: f32,
 
+    /// Sample this many periods ahead of the playback time, leading the
+    /// buffered data. Trades buffer safety (more extrapolation) for
+    /// responsiveness.
+    pub lookahead_periods: f32,
+
+    /// The largest delta time (seconds) a single step may advance by.
+    /// Negative delta times, from a clock going backwards, are treated
+    /// as zero.
+    pub max_delta_time: f64,
+
+    /// Pass each output to `Snapshot::quantize` with this step, e.g. to
+    /// snap positions to whole pixels rather than shimmering sub-pixel.
+    pub output_quantize: Option<f64>,
+
+    /// Interpolate at most this many times per second, holding the last
+    /// interpolation between, e.g. to save power on battery. The playback
+    /// time still advances every step.
+    pub max_interp_hz: Option<f64>,
+
+    /// Weighting of the debug measures in `Playback::last_quality`
+    pub quality_weights: QualityWeights,
+
     /// Clamp the playback time this many periods about the
     /// target time
     pub playback_clamp_periods: f32,
 
+    /// Rather than snapping the playback time onto the clamp, converge
+    /// onto it over this many periods, hastening or holding the playback
+    /// meanwhile. A longer correction, but without a visible jump.
+    pub soft_clamp_periods: Option<f32>,
+
     /// Begin slowing the playback when the playback time is
     /// this many periods ahead of the target time (positive)
     pub playback_slow_periods: f32,
     pub playback_slow_speed: f32,
 
+    /// Never slow or hasten the playback while the playback time is
+    /// within this many periods of the target time, overriding the
+    /// slow/fast thresholds. Reduces constant micro-corrections.
+    pub catchup_dead_zone_periods: f32,
+
+    /// Once the playback slows, hastens or returns to normal speed, hold
+    /// that timescale for at least this many periods before changing it
+    /// again, so that the timescale doesn't toggle from one arrival to the
+    /// next about a threshold. A debounce, complementing the dead zone.
+    pub min_scaling_hold_periods: Option<f32>,
+
+    /// How quickly velocity decays while extrapolating, passed to
+    /// `Snapshot::extrapolate`. By convention per period beyond the newest
+    /// snapshot, see `decayed_t`. 0.0 holds a constant velocity.
+    pub extrapolation_decay: f64,
+
+    /// Never let the playback time run more than this many periods past
+    /// the newest snapshot, however long the gap. An absolute bound on how
+    /// far ahead is guessed, unlike the per segment `t` cap.
+    pub max_extrapolation_periods: Option<f32>,
+
+    /// Ramp the timescale toward its target speed by no more than this
+    /// much per second, rather than switching speeds instantly. Smooths
+    /// the audible/visible effect of speed changes.
+    pub timescale_slew_per_second: Option<f64>,
+
+    /// Constrains which way the timescale may be corrected
+    pub correction_direction: CorrectionDirection,
+
+    /// Never correct the timescale, advancing the playback at exactly real
+    /// time, nor measure the catchup. For a local clock already in sync
+    /// with the remote (e.g. a shared process, or a deterministic tick).
+    /// The playback time is still clamped about the target time.
+    pub disable_timescale: bool,
+
+    /// Skip the playback clock and interpolation entirely, each step (of
+    /// every kind, e.g. `Playback::step_to`) returning exactly the newest
+    /// snapshot received. For debugging what the remote actually sent, or
+    /// comparing against interpolation.
+    pub raw_passthrough: bool,
+
+    /// Scale the catchup time by `Playback::catchup_confidence` when
+    /// computing the timescale, so that under packet loss the timescale
+    /// stays nearer 1.0 rather than chasing a target estimated from
+    /// stale data.
+    pub catchup_loss_confidence: bool,
+
     /// Begin hastening the playback when the playback time is
     /// this many periods behind of the target time (negative)
     pub playback_fast_periods: f32,
     pub playback_fast_speed: f32,
 }
 
-pub static SNAPSHOT_SETTINGS_DEFAULT: LazyLock<Settings> = LazyLock::new(|| Settings::default());
+pub static SNAPSHOT_SETTINGS_DEFAULT: LazyLock<Settings> = LazyLock::new(Settings::default);
 
 impl Default for Settings {
     fn default() -> Self {
         Settings {
             buf_duration: 2.0,
+            auto_buffer: false,
+            auto_buffer_margin_periods: 2.0,
+            warmup_snapshots: 0,
+            max_buffer_bytes: None,
+            max_buf_len: 1024,
             period: 200.0 / 1000.0, // T = 200ms
 
             dynamic_playback_time: true,
             dynamic_playback_jitter_duration: 2.0,
+            adaptive_ema_window: false,
+            jitter_spike_reset: None,
+            offset_unfreeze_periods: None,
+            offset_quantum_ms: None,
+            recent_deltas_len: 8,
+            on_time_collision: TimeCollision::KeepExisting,
 
             playback_clamp_periods: 1.0,
+            soft_clamp_periods: None,
             playback_fast_periods: 0.5,
             playback_fast_speed: 1.0 + 0.02,
             playback_slow_periods: -0.5,
             playback_slow_speed: 1.0 - 0.04,
+            catchup_dead_zone_periods: 0.0,
+            min_scaling_hold_periods: None,
+            timescale_slew_per_second: None,
+            extrapolation_decay: 0.0,
+            max_extrapolation_periods: None,
+            correction_direction: CorrectionDirection::Both,
+            disable_timescale: false,
+            raw_passthrough: false,
+            catchup_loss_confidence: false,
 
             playback_offset_periods: 1.0,
+            lookahead_periods: 0.0,
+            max_delta_time: 5.0,
+            output_quantize: None,
+            max_interp_hz: None,
+
+            quality_weights: QualityWeights::default(),
         }
     }
 }
 
 impl Settings {
+    /// A preset which prioritises latency over smoothness. Playback
+    /// targets the newest snapshot directly (zero offset) with a tight
+    /// clamp, so most steps will be extrapolating. Expect the occasional
+    /// pop under jitter or loss.
+    pub fn low_latency() -> Self {
+        Settings {
+            dynamic_playback_time: false,
+
+            playback_clamp_periods: 0.25,
+            playback_offset_periods: 0.0,
+
+            ..Settings::default()
+        }
+    }
+
+    /// Derive settings which fit an interpolation delay budget, given the
+    /// time period (seconds) between the remote sending two snapshots. The
+    /// base playback offset fills the budget, with the clamp and buffer
+    /// sized to suit. The dynamic offset may exceed the budget under
+    /// jitter.
+    ///
+    /// Errors if the budget is less than a period, since playback would
+    /// constantly extrapolate.
+    pub fn for_latency_budget(period: f64, budget_ms: f64) -> Result<Self, LatencyBudgetError> {
+        let offset_periods = (budget_ms / 1000.0 / period) as f32;
+        if offset_periods < 1.0 {
+            return Err(LatencyBudgetError {
+                minimum_ms: period * 1000.0,
+            });
+        }
+
+        // Never stray more than half the budget from the target
+        let clamp_periods = (offset_periods / 2.0).min(1.0);
+
+        // Room for the offset and the clamp, with a couple of periods spare
+        let defaults = Settings::default();
+        let buf_duration = defaults
+            .buf_duration
+            .max(period as f32 * (offset_periods + clamp_periods + 2.0));
+
+        Ok(Settings {
+            period,
+            buf_duration,
+
+            playback_clamp_periods: clamp_periods,
+            playback_offset_periods: offset_periods,
+
+            ..defaults
+        })
+    }
+
     pub fn playback_offset(&self) -> f32 {
         self.period as f32 * self.playback_offset_periods
     }
 
+    pub fn auto_buffer_margin(&self) -> f32 {
+        self.period as f32 * self.auto_buffer_margin_periods
+    }
+
+    pub fn offset_unfreeze(&self) -> Option<f32> {
+        self.offset_unfreeze_periods
+            .map(|periods| self.period as f32 * periods)
+    }
+
+    pub fn lookahead(&self) -> f32 {
+        self.period as f32 * self.lookahead_periods
+    }
+
+    pub fn max_extrapolation(&self) -> Option<f32> {
+        self.max_extrapolation_periods
+            .map(|periods| self.period as f32 * periods)
+    }
+
     pub fn playback_clamp(&self) -> f32 {
         self.period as f32 * self.playback_clamp_periods
     }
 
+    pub fn soft_clamp(&self) -> Option<f32> {
+        self.soft_clamp_periods
+            .map(|periods| self.period as f32 * periods)
+    }
+
     pub fn fast_threshold(&self) -> f32 {
         self.period as f32 * self.playback_fast_periods
     }
@@ -79,8 +383,77 @@ impl Settings {
         self.period as f32 * self.playback_slow_periods
     }
 
+    /// Convert a number of periods, as in the `*_periods` settings, into
+    /// seconds
+    pub fn periods_to_seconds(&self, periods: f32) -> f64 {
+        self.period * periods as f64
+    }
+
+    /// Convert seconds into a number of periods, as in the `*_periods`
+    /// settings
+    pub fn seconds_to_periods(&self, seconds: f64) -> f32 {
+        (seconds / self.period) as f32
+    }
+
     /// Packets per Second (dispatched by the remote)
     pub fn send_rate(&self) -> f64 {
-        1.0 / (self.period as f64)
+        1.0 / self.period
+    }
+
+    /// The number of snapshots `buf_duration` covers, capped at
+    /// `max_buf_len`. Check `is_buf_len_capped` to catch settings which
+    /// don't fit.
+    pub fn buf_len(&self) -> usize {
+        self.uncapped_buf_len().min(self.max_buf_len)
+    }
+
+    /// Whether `buf_duration` calls for more than `max_buf_len` snapshots,
+    /// i.e. the buffer holds less than `buf_duration` of snapshots
+    pub fn is_buf_len_capped(&self) -> bool {
+        self.uncapped_buf_len() > self.max_buf_len
+    }
+
+    /// Saturates rather than overflows, e.g. for a zero `period`
+    fn uncapped_buf_len(&self) -> usize {
+        (self.send_rate() as f32 * self.buf_duration).ceil() as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{LatencyBudgetError, Settings};
+
+    #[test]
+    fn periods_seconds_test() {
+        let settings = Settings {
+            period: 0.05,
+            ..Settings::default()
+        };
+
+        assert!((settings.periods_to_seconds(1.0) - 0.05).abs() < 1e-9);
+        assert!((settings.periods_to_seconds(-0.5) + 0.025).abs() < 1e-9);
+        assert!((settings.seconds_to_periods(0.1) - 2.0).abs() < 1e-6);
+
+        for periods in [0.0, 0.25, 1.0, 3.5, -2.0] {
+            let seconds = settings.periods_to_seconds(periods);
+            assert!((settings.seconds_to_periods(seconds) - periods).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn latency_budget_test() {
+        let settings = Settings::for_latency_budget(0.05, 150.0).unwrap();
+        assert!(settings.playback_offset() as f64 <= 0.150 + 1e-6);
+        assert!(settings.playback_offset() as f64 >= settings.period);
+        assert!(settings.playback_clamp() <= settings.playback_offset() / 2.0 + 1e-6);
+        assert!(
+            settings.buf_duration
+                >= settings.playback_offset() + settings.playback_clamp() + settings.period as f32
+        );
+
+        assert_eq!(
+            Settings::for_latency_budget(0.2, 150.0).err(),
+            Some(LatencyBudgetError { minimum_ms: 200.0 })
+        );
     }
 }
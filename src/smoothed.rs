@@ -0,0 +1,66 @@
+use num_traits::Float;
+
+/// A value which exponentially approaches its target, independent of the
+/// snapshot rate. For cosmetic fields (e.g. a speedometer) where
+/// smoothness matters more than accuracy, kept alongside the `Playback`
+/// and stepped toward each interpolated output.
+#[derive(Clone, Copy, Debug)]
+pub struct Smoothed<F> {
+    /// The time taken to close all but `1 / e` (~37%) of the distance to
+    /// a constant target (seconds). Zero snaps straight to the target.
+    pub time_constant: F,
+
+    pub value: F,
+}
+
+impl<F: Float> Smoothed<F> {
+    pub fn new(time_constant: F, value: F) -> Self {
+        Self {
+            time_constant,
+            value,
+        }
+    }
+
+    /// Approach `target` by passing in how much time has passed since the
+    /// last step (seconds), returning the new value.
+    pub fn step(&mut self, target: F, delta_time: F) -> F {
+        if self.time_constant <= F::zero() {
+            self.value = target;
+            return self.value;
+        }
+
+        let alpha = F::one() - (-delta_time / self.time_constant).exp();
+        self.value = self.value + (target - self.value) * alpha;
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Smoothed;
+
+    #[test]
+    fn smoothed_test() {
+        let mut smoothed = Smoothed::new(0.5, 0.0);
+
+        // One time constant covers 1 - 1/e of the distance, regardless
+        // of the step size
+        for _ in 0..30 {
+            smoothed.step(10.0, 0.5 / 30.0);
+        }
+        let expected = 10.0 * (1.0 - (-1.0_f64).exp());
+        assert!((smoothed.value - expected).abs() < 1e-9);
+
+        smoothed.step(10.0, 0.5);
+        let expected = 10.0 * (1.0 - (-2.0_f64).exp());
+        assert!((smoothed.value - expected).abs() < 1e-9);
+
+        // Converges without overshooting
+        for _ in 0..1000 {
+            assert!(smoothed.step(10.0, 0.1) <= 10.0);
+        }
+        assert!((smoothed.value - 10.0).abs() < 1e-9);
+
+        assert_eq!(Smoothed::new(0.0, 0.0).step(3.0, 0.01), 3.0);
+    }
+}
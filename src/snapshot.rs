@@ -1,6 +1,13 @@
+use std::f64::consts::{PI, TAU};
+
 use num_traits::{Euclid, Float};
 
 pub trait Snapshot: Clone {
+    /// Whether playback may extrapolate beyond the newest snapshot. Types
+    /// for which extrapolation produces garbage (e.g. discrete or
+    /// quantized data) hold on the newest snapshot instead.
+    const EXTRAPOLATABLE: bool = true;
+
     fn interpolate(t: f64, from: &Self, to: &Self) -> Self;
 
     /// Indicate the time, in seconds, at which this packet was
@@ -8,38 +15,436 @@ pub trait Snapshot: Clone {
     /// the remote's time, so from which point this is measured doesn't
     /// matter, so long as it's consistent.
     fn remote_time(&self) -> f64;
+
+    /// The schema version of this snapshot. Snapshots of differing
+    /// versions (e.g. during a rolling server update) are never
+    /// interpolated between, playback snaps to the newer one instead.
+    fn version(&self) -> u32 {
+        0
+    }
+
+    /// The entity this snapshot's position is relative to, if attached
+    /// (e.g. a gun held by a player). Snapshots of differing parents are
+    /// never interpolated between, playback snaps to the newer one
+    /// instead, so that the attachment doesn't slide between parents.
+    fn parent_id(&self) -> Option<u64> {
+        None
+    }
+
+    /// The remote time (seconds) beyond which this snapshot no longer
+    /// holds, e.g. a projectile which despawns. Once the playback time
+    /// passes it, playback stops emitting output from this snapshot.
+    /// Valid indefinitely by default.
+    fn valid_until(&self) -> Option<f64> {
+        None
+    }
+
+    /// Enforce any invariants on an interpolated snapshot (e.g. health
+    /// no greater than 100, position within the world bounds). Called
+    /// after interpolating, which is especially important when
+    /// extrapolating. Does nothing by default.
+    fn clamp(&mut self) {}
+
+    /// Snap an output to a grid of `step`, e.g. rounding positions to
+    /// whole pixels, see `Settings::output_quantize`. Called last on each
+    /// output. Does nothing by default.
+    fn quantize(&mut self, step: f64) {
+        let _ = step;
+    }
+
+    /// Interpolate between `from` and `to` along a curve which also
+    /// passes through `prev`, the snapshot preceding `from`, capturing
+    /// acceleration. e.g. with `quad_bezier`. `Playback::step` uses this
+    /// when there's a preceding snapshot. Linear by default.
+    fn interpolate_quadratic(t: f64, prev: &Self, from: &Self, to: &Self) -> Self {
+        let _ = prev;
+        Self::interpolate(t, from, to)
+    }
+
+    /// Extrapolate beyond `to`, where `t` exceeds 1.0. `decay` is
+    /// `Settings::extrapolation_decay`, which implementers may use to coast
+    /// to a stop rather than hold a constant velocity, e.g. with
+    /// `decayed_t`. Linear by default, ignoring `decay`.
+    fn extrapolate(from: &Self, to: &Self, t: f64, decay: f64) -> Self {
+        let _ = decay;
+        Self::interpolate(t, from, to)
+    }
+
+    /// Visit each scalar value to be smoothed by a playback's
+    /// `OneEuroFilter`, in a consistent order, see
+    /// `Playback::with_one_euro_filter`. Visits none by default.
+    fn filter_values(&mut self, visit: &mut dyn FnMut(&mut f64)) {
+        let _ = visit;
+    }
+
+    /// The distance travelled between two snapshots, e.g. the euclidean
+    /// distance between their positions, for
+    /// `Playback::with_arc_length_reparam`. Zero by default, which leaves
+    /// the interpolation parameter as is.
+    fn arc_length(&self, other: &Self) -> f64 {
+        let _ = other;
+        0.0
+    }
+
+    /// Whether two snapshots' values are within `epsilon` of each other,
+    /// for `Playback::step_on_change` to skip redundant outputs. Never by
+    /// default, so every output is emitted.
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        let _ = (other, epsilon);
+        false
+    }
+
+    /// Roughly how many bytes this snapshot occupies, including any heap
+    /// allocations (e.g. variable length arrays), for bounding the buffer
+    /// by `Settings::max_buffer_bytes`. The inline size by default.
+    fn approx_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+}
+
+/// Tuples of snapshots interpolate each element independently. The
+/// first element is authoritative for the `remote_time`, `version`,
+/// `parent_id` and `valid_until`, the remaining elements' are ignored.
+/// Only extrapolatable should every element be.
+macro_rules! impl_snapshot_tuple {
+    ($($element:ident $index:tt),+) => {
+        impl<$($element: Snapshot),+> Snapshot for ($($element,)+) {
+            const EXTRAPOLATABLE: bool = true $(&& $element::EXTRAPOLATABLE)+;
+
+            fn interpolate(t: f64, from: &Self, to: &Self) -> Self {
+                ($($element::interpolate(t, &from.$index, &to.$index),)+)
+            }
+
+            fn remote_time(&self) -> f64 {
+                self.0.remote_time()
+            }
+
+            fn version(&self) -> u32 {
+                self.0.version()
+            }
+
+            fn parent_id(&self) -> Option<u64> {
+                self.0.parent_id()
+            }
+
+            fn valid_until(&self) -> Option<f64> {
+                self.0.valid_until()
+            }
+
+            fn clamp(&mut self) {
+                $(self.$index.clamp();)+
+            }
+
+            fn quantize(&mut self, step: f64) {
+                $(self.$index.quantize(step);)+
+            }
+
+            fn interpolate_quadratic(t: f64, prev: &Self, from: &Self, to: &Self) -> Self {
+                ($($element::interpolate_quadratic(t, &prev.$index, &from.$index, &to.$index),)+)
+            }
+
+            fn extrapolate(from: &Self, to: &Self, t: f64, decay: f64) -> Self {
+                ($($element::extrapolate(&from.$index, &to.$index, t, decay),)+)
+            }
+
+            fn filter_values(&mut self, visit: &mut dyn FnMut(&mut f64)) {
+                $(self.$index.filter_values(visit);)+
+            }
+
+            fn arc_length(&self, other: &Self) -> f64 {
+                0.0 $(+ self.$index.arc_length(&other.$index))+
+            }
+
+            fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+                true $(&& self.$index.approx_eq(&other.$index, epsilon))+
+            }
+
+            fn approx_size(&self) -> usize {
+                0 $(+ self.$index.approx_size())+
+            }
+        }
+    };
+}
+
+impl_snapshot_tuple!(A 0, B 1);
+impl_snapshot_tuple!(A 0, B 1, C 2);
+impl_snapshot_tuple!(A 0, B 1, C 2, D 3);
+
+/// A snapshot which can be interpolated within a moving reference frame
+/// (a moving platform, a vehicle interior), e.g. interpolating a position
+/// relative to its parent, then transforming it out by the frame.
+/// See `Playback::step_in_frame`.
+pub trait FrameSnapshot: Snapshot {
+    /// Caller defined reference frame
+    type Frame;
+
+    fn interpolate_in_frame(t: f64, from: &Self, to: &Self, frame: &Self::Frame) -> Self;
 }
 
 /// Interpolate an angle in degrees always taking the shortest distance around a circle
+pub fn lerp_angle<F: Float + Euclid>(a: F, b: F, t: F) -> F {
+    lerp_turn(a, b, t, F::from(180.0).unwrap(), F::from(360.0).unwrap())
+}
+
+/// Interpolate an angle in radians always taking the shortest distance around a circle
+pub fn lerp_angle_rad<F: Float + Euclid>(a: F, b: F, t: F) -> F {
+    lerp_turn(a, b, t, F::from(PI).unwrap(), F::from(TAU).unwrap())
+}
+
+/// Interpolate a value which wraps around between 0 and `period`, e.g. a
+/// position along a looping track, always taking the shortest distance
+/// around the loop. `lerp_angle` is the case of a period of 360.
+pub fn lerp_wrapped<F: Float + Euclid>(a: F, b: F, t: F, period: F) -> F {
+    lerp_turn(a, b, t, period / F::from(2.0).unwrap(), period)
+}
+
+/// Interpolate a rotation between two poses of an angle (radians) and
+/// angular velocity (radians per second), along the cubic Hermite curve
+/// with the angular velocities as tangents, taking the shortest arc. Unlike
+/// interpolating each independently, the returned angle and angular
+/// velocity agree, and the rotation is smooth across segments.
+/// `duration` is the time between the two poses (seconds).
+///
+/// Returns `(angle, angular_velocity)`, the angle within [0, 2π).
+pub fn interpolate_pose<F: Float + Euclid>(
+    t: F,
+    from_angle: F,
+    from_omega: F,
+    to_angle: F,
+    to_omega: F,
+    duration: F,
+) -> (F, F) {
+    let one = F::one();
+    let two = F::from(2.0).unwrap();
+    let three = F::from(3.0).unwrap();
+    let four = F::from(4.0).unwrap();
+    let six = F::from(6.0).unwrap();
+    let pi = F::from(PI).unwrap();
+    let tau = F::from(TAU).unwrap();
+
+    // The shortest signed arc, within [-π, π)
+    let delta = (to_angle - from_angle + pi).rem_euclid(&tau) - pi;
+    let m0 = from_omega * duration;
+    let m1 = to_omega * duration;
+
+    let t2 = t * t;
+    let t3 = t2 * t;
+    let angle = (t3 - two * t2 + t) * m0 + (three * t2 - two * t3) * delta + (t3 - t2) * m1;
+    let rate = (three * t2 - four * t + one) * m0
+        + (six * t - six * t2) * delta
+        + (three * t2 - two * t) * m1;
+
+    let omega = if duration > F::zero() {
+        rate / duration
+    } else {
+        lerp(from_omega, to_omega, t)
+    };
+    ((from_angle + angle).rem_euclid(&tau), omega)
+}
+
+/// Interpolate an angle always taking the shortest distance around a
+/// circle, in whichever units make up `full_turn`.
 // TODO: Could be much better branch prediction wise?
-pub fn lerp_angle<F: Float + Euclid>(a: F, b: F, mut t: F) -> F {
+fn lerp_turn<F: Float + Euclid>(a: F, b: F, mut t: F, half_turn: F, full_turn: F) -> F {
     let mut low = a;
     let mut high = b;
     let mut delta = high - low;
-    if delta > (F::from(180.0)).unwrap() {
+    if delta > half_turn {
         t = F::one() + (t * -F::one());
         low = b;
-        high = a + F::from(360.0).unwrap();
+        high = a + full_turn;
         delta = high - low;
-    } else if delta < -F::from(180.0).unwrap() {
+    } else if delta < -half_turn {
         low = a;
-        high = b + F::from(360.0).unwrap();
+        high = b + full_turn;
         delta = high - low;
     }
-    (low + (t * delta)).rem_euclid(&F::from(360.0).unwrap())
+    (low + (t * delta)).rem_euclid(&full_turn)
+}
+
+/// Interpolate a 2D rotation stored as a unit complex number `[cos, sin]`
+/// by lerping the components and renormalizing. Cheaper than `slerp2`,
+/// though the angular speed isn't constant. Returns `a` should the two
+/// be exactly opposite at the midpoint.
+pub fn nlerp2<F: Float>(a: [F; 2], b: [F; 2], t: F) -> [F; 2] {
+    let re = lerp(a[0], b[0], t);
+    let im = lerp(a[1], b[1], t);
+    let len = re.hypot(im);
+    if len == F::zero() {
+        return a;
+    }
+    [re / len, im / len]
+}
+
+/// Interpolate a 2D rotation stored as a unit complex number `[cos, sin]`
+/// at a constant angular speed, always taking the shortest way around.
+pub fn slerp2<F: Float>(a: [F; 2], b: [F; 2], t: F) -> [F; 2] {
+    // The signed angle from a to b
+    let dot = a[0] * b[0] + a[1] * b[1];
+    let cross = a[0] * b[1] - a[1] * b[0];
+    let (sin, cos) = (cross.atan2(dot) * t).sin_cos();
+
+    // Rotate a by that fraction of the angle
+    [a[0] * cos - a[1] * sin, a[0] * sin + a[1] * cos]
+}
+
+/// Interpolate a (latitude, longitude) position in degrees, wrapping the
+/// longitude the shortest way around the globe so that crossing the
+/// antimeridian doesn't sweep the long way round. The longitude is
+/// returned within [-180, 180).
+pub fn lerp_lat_long<F: Float + Euclid>(from: (F, F), to: (F, F), t: F) -> (F, F) {
+    let half_turn = F::from(180.0).unwrap();
+
+    let lat = lerp(from.0, to.0, t);
+    let lon = lerp_angle(from.1 + half_turn, to.1 + half_turn, t) - half_turn;
+    (lat, lon)
+}
+
+/// Interpolate a `(min, max)` range, e.g. one axis of a bounding box, by
+/// its center and extent rather than each end independently. The same as
+/// lerping either end while interpolating, but extrapolating a shrinking
+/// range collapses it to its center rather than inverting it, so min ≤ max
+/// always holds.
+///
+/// For an axis-aligned bounding box, interpolate each axis with this in
+/// `Snapshot::interpolate`, and the default `Snapshot::extrapolate` stays
+/// valid too.
+pub fn lerp_range<F: Float>(from: (F, F), to: (F, F), t: F) -> (F, F) {
+    let two = F::from(2.0).unwrap();
+
+    let center = lerp((from.0 + from.1) / two, (to.0 + to.1) / two, t);
+    let extent = lerp((from.1 - from.0) / two, (to.1 - to.0) / two, t).max(F::zero());
+    (center - extent, center + extent)
 }
 
 pub fn lerp<F: Float>(a: F, b: F, t: F) -> F {
     a + (t * (b - a))
 }
 
+/// How `lerp_bool` blends a flag part way through a segment
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BoolBlend {
+    /// `from` until `t` reaches the given threshold, then `to`
+    SnapAt(f64),
+    /// Set if either is set, e.g. so a brief `is_firing` isn't missed
+    Or,
+    /// Set only if both are set
+    And,
+    /// `from` until the segment ends
+    HoldFrom,
+    /// `to` as soon as the segment begins
+    HoldTo,
+}
+
+/// Interpolate a flag, e.g. `is_grounded`, according to `mode`. At either
+/// end of the segment (`t` of 0.0 or below, 1.0 or above) the flag is
+/// exactly `from` or `to`, whichever the mode.
+pub fn lerp_bool(from: bool, to: bool, t: f64, mode: BoolBlend) -> bool {
+    if t <= 0.0 {
+        return from;
+    }
+    if t >= 1.0 {
+        return to;
+    }
+
+    match mode {
+        BoolBlend::SnapAt(threshold) => {
+            if t < threshold {
+                from
+            } else {
+                to
+            }
+        }
+        BoolBlend::Or => from || to,
+        BoolBlend::And => from && to,
+        BoolBlend::HoldFrom => from,
+        BoolBlend::HoldTo => to,
+    }
+}
+
+/// Interpolate a value which should only ever increase (a score, a
+/// distance travelled), never dropping below `prev_output`. Prevents
+/// visual count-downs when a late packet or a clamp steps `t` backwards.
+///
+/// `Snapshot::interpolate` is stateless, so the previous output must be
+/// threaded through by the caller. e.g. keep the last rendered value
+/// alongside the `Playback`, and share it with the `Snapshot` impl through
+/// a `Cell` (or a field on the snapshot) which is updated after each step.
+pub fn lerp_monotonic<F: Float>(prev_output: F, a: F, b: F, t: F) -> F {
+    lerp(a, b, t).max(prev_output)
+}
+
+/// Interpolate between `from` and `to` along the quadratic Bezier curve
+/// which also passes through `prev`, assuming the three are evenly spaced
+/// in time. Captures acceleration which `lerp` would flatten into facets.
+pub fn quad_bezier<F: Float>(prev: F, from: F, to: F, t: F) -> F {
+    let two = F::from(2.0).unwrap();
+    let four = F::from(4.0).unwrap();
+
+    // Matches the tangent at `from` of the parabola through all three
+    let control = from + (to - prev) / four;
+    let u = F::one() - t;
+    u * u * from + two * u * t * control + t * t * to
+}
+
+/// Interpolate between `p1` and `p2` along a monotone cubic Hermite spline
+/// (Fritsch–Carlson) through the four evenly spaced samples. Smooth like
+/// Catmull-Rom, but never overshooting `p1` or `p2`, for values which must
+/// stay within bounds (e.g. a health bar).
+pub fn monotone_cubic<F: Float>(p0: F, p1: F, p2: F, p3: F, t: F) -> F {
+    let zero = F::zero();
+    let two = F::from(2.0).unwrap();
+    let three = F::from(3.0).unwrap();
+    let nine = F::from(9.0).unwrap();
+
+    let (d0, d1, d2) = (p1 - p0, p2 - p1, p3 - p2);
+    // Flat at local extrema, otherwise the mean of the secants either side
+    let tangent = |a: F, b: F| if a * b <= zero { zero } else { (a + b) / two };
+    let (mut m1, mut m2) = (tangent(d0, d1), tangent(d1, d2));
+    if d1 == zero {
+        m1 = zero;
+        m2 = zero;
+    } else {
+        // Shrink the tangents into the region which can't overshoot
+        let (a, b) = (m1 / d1, m2 / d1);
+        let magnitude = a * a + b * b;
+        if magnitude > nine {
+            let tau = three / magnitude.sqrt();
+            m1 = tau * a * d1;
+            m2 = tau * b * d1;
+        }
+    }
+
+    let t2 = t * t;
+    let t3 = t2 * t;
+    // Relative to `p1`, so that flat segments are exactly flat
+    p1 + (three * t2 - two * t3) * d1 + (t3 - two * t2 + t) * m1 + (t3 - t2) * m2
+}
+
+/// Remap an extrapolating `t` (beyond 1.0) such that the velocity decays
+/// exponentially at `decay` per period beyond `to`, coasting to a stop
+/// at most `1 / decay` periods beyond. `t` within 0.0..=1.0, or a `decay`
+/// of zero, are unchanged.
+pub fn decayed_t<F: Float>(t: F, decay: F) -> F {
+    if t <= F::one() || decay <= F::zero() {
+        return t;
+    }
+    F::one() + (F::one() - (-decay * (t - F::one())).exp()) / decay
+}
+
 pub fn linear_map<F: Float>(x: F, a: F, b: F, c: F, d: F) -> F {
     c + (x - a) * (d - c) / (b - a)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::snapshot::{lerp, lerp_angle, linear_map};
+    use std::f64::consts::{FRAC_PI_2, PI, TAU};
+
+    use crate::snapshot::{
+        BoolBlend, Snapshot, decayed_t, interpolate_pose, lerp, lerp_angle, lerp_angle_rad,
+        lerp_bool, lerp_lat_long, lerp_monotonic, lerp_range, lerp_wrapped, linear_map,
+        monotone_cubic, nlerp2, quad_bezier, slerp2,
+    };
 
     #[test]
     fn linear_map_test() {
@@ -69,6 +474,69 @@ mod tests {
         assert_eq!(lerp(4.0, 0.0, 2.0), -4.0);
     }
 
+    #[test]
+    fn lerp_bool_test() {
+        let across =
+            |from, to, mode| [0.0, 0.25, 0.5, 0.75, 1.0].map(|t| lerp_bool(from, to, t, mode));
+
+        let snap = BoolBlend::SnapAt(0.5);
+        assert_eq!(across(false, true, snap), [false, false, true, true, true]);
+        assert_eq!(across(true, false, snap), [true, true, false, false, false]);
+        assert_eq!(
+            across(false, true, BoolBlend::SnapAt(0.8)),
+            [false, false, false, false, true]
+        );
+
+        assert_eq!(
+            across(false, true, BoolBlend::Or),
+            [false, true, true, true, true]
+        );
+        assert_eq!(
+            across(true, false, BoolBlend::Or),
+            [true, true, true, true, false]
+        );
+
+        assert_eq!(
+            across(false, true, BoolBlend::And),
+            [false, false, false, false, true]
+        );
+        assert_eq!(
+            across(true, false, BoolBlend::And),
+            [true, false, false, false, false]
+        );
+
+        assert_eq!(
+            across(false, true, BoolBlend::HoldFrom),
+            [false, false, false, false, true]
+        );
+        assert_eq!(
+            across(true, false, BoolBlend::HoldTo),
+            [true, false, false, false, false]
+        );
+
+        // Unchanged flags are unaffected by the mode
+        for mode in [
+            snap,
+            BoolBlend::Or,
+            BoolBlend::And,
+            BoolBlend::HoldFrom,
+            BoolBlend::HoldTo,
+        ] {
+            assert_eq!(across(true, true, mode), [true; 5]);
+            assert_eq!(across(false, false, mode), [false; 5]);
+        }
+    }
+
+    #[test]
+    fn lerp_monotonic_test() {
+        assert_eq!(lerp_monotonic(0.0, 0.0, 10.0, 0.5), 5.0);
+        assert_eq!(lerp_monotonic(5.0, 0.0, 10.0, 0.8), 8.0);
+
+        // A backwards correction is held rather than counting down
+        assert_eq!(lerp(0.0, 10.0, 0.6), 6.0);
+        assert_eq!(lerp_monotonic(8.0, 0.0, 10.0, 0.6), 8.0);
+    }
+
     #[test]
     fn heading_test() {
         //Normal lerp (without any negatives though)
@@ -99,4 +567,327 @@ mod tests {
         assert_eq!(lerp_angle(40.0, 350.0, 0.2), 30.0);
         assert_eq!(lerp_angle(40.0, 350.0, 0.1), 35.0);
     }
+
+    #[test]
+    fn heading_rad_test() {
+        let assert_close = |a: f64, b: f64| assert!((a - b).abs() < 1e-12, "{a} != {b}");
+
+        //Normal lerp (without any negatives though)
+        assert_close(lerp_angle_rad(0.0, 1.0, 1.0), 1.0);
+        assert_close(lerp_angle_rad(0.0, 1.0, 0.0), 0.0);
+        assert_close(lerp_angle_rad(0.0, 1.0, 0.5), 0.5);
+        assert_close(lerp_angle_rad(0.0, 1.0, 0.25), 0.25);
+        assert_close(lerp_angle_rad(1.0, 0.0, 0.75), 0.25);
+        assert_close(lerp_angle_rad(0.0, FRAC_PI_2, 0.5), FRAC_PI_2 / 2.0);
+
+        //Heading lerp, wrapping around 2π
+        let a = TAU - 0.2;
+        let b = 0.6;
+        assert_close(lerp_angle_rad(a, b, 0.5), 0.2);
+        assert_close(lerp_angle_rad(a, b, 0.25), 0.0);
+        assert_close(lerp_angle_rad(a, b, 0.125), TAU - 0.1);
+
+        assert_close(lerp_angle_rad(b, a, 0.5), 0.2);
+        assert_close(lerp_angle_rad(b, a, 0.75), 0.0);
+        assert_close(lerp_angle_rad(b, a, 0.875), TAU - 0.1);
+
+        // Exactly half a turn apart doesn't wrap
+        assert_close(lerp_angle_rad(0.0, PI, 0.5), FRAC_PI_2);
+    }
+
+    #[test]
+    fn wrapped_test() {
+        let assert_close = |a: f64, b: f64| assert!((a - b).abs() < 1e-9, "{a} != {b}");
+
+        for period in [1.0, 10.0, 360.0, 2500.0] {
+            // Doesn't wrap
+            let a = period * 0.2;
+            let b = period * 0.4;
+            assert_close(lerp_wrapped(a, b, 0.5, period), period * 0.3);
+            assert_close(lerp_wrapped(b, a, 0.25, period), period * 0.35);
+
+            // Wraps across the seam, forward and backward
+            let a = period * 0.9;
+            let b = period * 0.1;
+            assert_close(lerp_wrapped(a, b, 0.0, period), a);
+            assert_close(lerp_wrapped(a, b, 0.25, period), period * 0.95);
+            assert_close(lerp_wrapped(a, b, 0.5, period), 0.0);
+            assert_close(lerp_wrapped(a, b, 0.75, period), period * 0.05);
+            assert_close(lerp_wrapped(a, b, 1.0, period), b);
+            assert_close(lerp_wrapped(b, a, 0.25, period), period * 0.05);
+            assert_close(lerp_wrapped(b, a, 0.75, period), period * 0.95);
+        }
+
+        // Agrees with the angle case
+        assert_eq!(
+            lerp_wrapped(350.0, 40.0, 0.1, 360.0),
+            lerp_angle(350.0, 40.0, 0.1)
+        );
+    }
+
+    #[test]
+    fn range_test() {
+        #[derive(Clone, Debug)]
+        struct Aabb {
+            time: f64,
+            min: [f64; 3],
+            max: [f64; 3],
+        }
+
+        impl Snapshot for Aabb {
+            fn interpolate(t: f64, from: &Self, to: &Self) -> Self {
+                let mut output = Aabb {
+                    time: lerp(from.time, to.time, t),
+                    min: [0.0; 3],
+                    max: [0.0; 3],
+                };
+                for axis in 0..3 {
+                    (output.min[axis], output.max[axis]) = lerp_range(
+                        (from.min[axis], from.max[axis]),
+                        (to.min[axis], to.max[axis]),
+                        t,
+                    );
+                }
+                output
+            }
+
+            fn remote_time(&self) -> f64 {
+                self.time
+            }
+        }
+
+        // Shrinking fast on x, growing on y, moving but constant on z
+        let from = Aabb {
+            time: 0.0,
+            min: [0.0, 0.0, 0.0],
+            max: [10.0, 1.0, 2.0],
+        };
+        let to = Aabb {
+            time: 0.2,
+            min: [4.0, -1.0, 5.0],
+            max: [6.0, 2.0, 7.0],
+        };
+
+        // Lerps the ends while interpolating
+        let half = Aabb::interpolate(0.5, &from, &to);
+        assert_eq!(half.min, [2.0, -0.5, 2.5]);
+        assert_eq!(half.max, [8.0, 1.5, 4.5]);
+
+        for i in 0..=250 {
+            let t = i as f64 / 100.0;
+            let output = Aabb::extrapolate(&from, &to, t, 0.0);
+            for axis in 0..3 {
+                assert!(output.min[axis] <= output.max[axis], "{output:?}");
+            }
+        }
+
+        // Collapsed to its center rather than inverted
+        let beyond = Aabb::extrapolate(&from, &to, 2.5, 0.0);
+        assert_eq!((beyond.min[0], beyond.max[0]), (5.0, 5.0));
+    }
+
+    #[test]
+    fn monotone_cubic_test() {
+        // Passes through the samples either end
+        assert_eq!(monotone_cubic(0.0, 1.0, 3.0, 4.0, 0.0), 1.0);
+        assert_eq!(monotone_cubic(0.0, 1.0, 3.0, 4.0, 1.0), 3.0);
+        // Linear samples stay linear
+        assert!((monotone_cubic(0.0, 1.0, 2.0, 3.0, 0.25_f64) - 1.25).abs() < 1e-12);
+
+        // Non-monotone sequences, where Catmull-Rom would overshoot
+        let sequences = [
+            [0.0, 1.0, 0.9, 5.0],
+            [10.0, 0.0, 1.0, -10.0],
+            [0.0, 100.0, 100.0, 0.0],
+            [0.0, 0.0, 1.0, 1.0],
+            [-5.0, 1.0, 1.1, 0.0],
+        ];
+        for [p0, p1, p2, p3] in sequences {
+            let (low, high) = (f64::min(p1, p2), f64::max(p1, p2));
+            for i in 0..=100 {
+                let value = monotone_cubic(p0, p1, p2, p3, i as f64 / 100.0);
+                assert!(
+                    (low..=high).contains(&value),
+                    "{value} outside {low}..={high}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn pose_test() {
+        let assert_close = |a: f64, b: f64| assert!((a - b).abs() < 1e-9, "{a} != {b}");
+
+        // Matches the poses at either end
+        assert_close(interpolate_pose(0.0, 1.0, 2.0, 1.5, 3.0, 0.25).0, 1.0);
+        assert_close(interpolate_pose(0.0, 1.0, 2.0, 1.5, 3.0, 0.25).1, 2.0);
+        assert_close(interpolate_pose(1.0, 1.0, 2.0, 1.5, 3.0, 0.25).0, 1.5);
+        assert_close(interpolate_pose(1.0, 1.0, 2.0, 1.5, 3.0, 0.25).1, 3.0);
+
+        // Across the seam the short way
+        assert_close(interpolate_pose(0.5, TAU - 0.1, 0.8, 0.1, 0.8, 0.25).0, 0.0);
+
+        // A spin accelerating at 4 rad/s², sampled every 250ms
+        let accel = 4.0;
+        let period = 0.25;
+        let pose = |time: f64| ((accel * time * time / 2.0).rem_euclid(TAU), accel * time);
+
+        // The largest change in angular velocity between consecutive frames
+        let roughness = |sample: &dyn Fn(f64) -> f64| {
+            let dt = 1.0 / 240.0;
+            let arc = |a: f64, b: f64| (b - a + PI).rem_euclid(TAU) - PI;
+            let mut last_omega: Option<f64> = None;
+            let mut roughness: f64 = 0.0;
+            for frame in 0..480 {
+                let time = frame as f64 * dt;
+                let omega = arc(sample(time), sample(time + dt)) / dt;
+                if let Some(last_omega) = last_omega {
+                    roughness = roughness.max((omega - last_omega).abs());
+                }
+                last_omega = Some(omega);
+            }
+            roughness
+        };
+
+        let segment = |time: f64| {
+            let from = (time / period).floor() * period;
+            ((time - from) / period, pose(from), pose(from + period))
+        };
+        let hermite = roughness(&|time| {
+            let (t, from, to) = segment(time);
+            interpolate_pose(t, from.0, from.1, to.0, to.1, period).0
+        });
+        let linear = roughness(&|time| {
+            let (t, from, to) = segment(time);
+            lerp_angle_rad(from.0, to.0, t)
+        });
+
+        assert!(hermite < linear * 0.1, "{hermite} {linear}");
+    }
+
+    #[test]
+    fn lat_long_test() {
+        assert_eq!(lerp_lat_long((10.0, 20.0), (30.0, 40.0), 0.5), (20.0, 30.0));
+        assert_eq!(lerp_lat_long((10.0, 20.0), (30.0, 40.0), 0.0), (10.0, 20.0));
+        assert_eq!(lerp_lat_long((10.0, 20.0), (30.0, 40.0), 1.0), (30.0, 40.0));
+
+        // Across the antimeridian
+        assert_eq!(
+            lerp_lat_long((0.0, 170.0), (10.0, -170.0), 0.5),
+            (5.0, -180.0)
+        );
+        assert_eq!(
+            lerp_lat_long((0.0, 170.0), (0.0, -170.0), 0.25),
+            (0.0, 175.0)
+        );
+        assert_eq!(
+            lerp_lat_long((0.0, -170.0), (0.0, 170.0), 0.25),
+            (0.0, -175.0)
+        );
+    }
+
+    #[test]
+    fn tuple_test() {
+        #[derive(Clone, Debug, PartialEq)]
+        struct Position {
+            time: f64,
+            x: f64,
+        }
+
+        impl Snapshot for Position {
+            fn interpolate(t: f64, from: &Self, to: &Self) -> Self {
+                Position {
+                    time: lerp(from.time, to.time, t),
+                    x: lerp(from.x, to.x, t),
+                }
+            }
+
+            fn remote_time(&self) -> f64 {
+                self.time
+            }
+        }
+
+        #[derive(Clone, Debug, PartialEq)]
+        struct Heading(f64);
+
+        impl Snapshot for Heading {
+            fn interpolate(t: f64, from: &Self, to: &Self) -> Self {
+                Heading(lerp_angle(from.0, to.0, t))
+            }
+
+            fn remote_time(&self) -> f64 {
+                // Not authoritative within a tuple
+                f64::NAN
+            }
+        }
+
+        let from = (Position { time: 1.0, x: 0.0 }, Heading(350.0));
+        let to = (Position { time: 2.0, x: 4.0 }, Heading(10.0));
+
+        let output = Snapshot::interpolate(0.25, &from, &to);
+        assert_eq!(output.0, Position { time: 1.25, x: 1.0 });
+        assert_eq!(output.1, Heading(355.0));
+        assert_eq!(output.remote_time(), 1.25);
+    }
+
+    #[test]
+    fn quad_bezier_test() {
+        assert_eq!(quad_bezier(0.0, 1.0, 4.0, 0.0), 1.0);
+        assert_eq!(quad_bezier(0.0, 1.0, 4.0, 1.0), 4.0);
+
+        // x = t^2 sampled at -1, 0 and 1 is reproduced exactly
+        for t in [0.25_f64, 0.5, 0.75] {
+            assert!((quad_bezier(1.0, 0.0, 1.0, t) - t * t).abs() < 1e-9);
+        }
+
+        // Linear data stays linear
+        assert!((quad_bezier(0.0_f64, 1.0, 2.0, 0.5) - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn complex_rotation_test() {
+        let complex = |degrees: f64| {
+            let (sin, cos) = degrees.to_radians().sin_cos();
+            [cos, sin]
+        };
+        let degrees = |c: [f64; 2]| c[1].atan2(c[0]).to_degrees().rem_euclid(360.0);
+        let angle_eq = |a: f64, b: f64| {
+            let delta = (a - b).rem_euclid(360.0);
+            delta < 1e-6 || 360.0 - delta < 1e-6
+        };
+
+        for (a, b) in [(10.0, 80.0), (350.0, 10.0), (10.0, 350.0), (90.0, 270.1)] {
+            for t in [0.0, 0.25, 0.5, 0.75, 1.0] {
+                let slerped = slerp2(complex(a), complex(b), t);
+                let nlerped = nlerp2(complex(a), complex(b), t);
+
+                assert!((slerped[0].hypot(slerped[1]) - 1.0).abs() < 1e-9);
+                assert!((nlerped[0].hypot(nlerped[1]) - 1.0).abs() < 1e-9);
+
+                assert!(angle_eq(degrees(slerped), lerp_angle(a, b, t)));
+            }
+
+            // nlerp agrees at the ends and midpoint
+            for t in [0.0, 0.5, 1.0] {
+                let nlerped = nlerp2(complex(a), complex(b), t);
+                assert!(angle_eq(degrees(nlerped), lerp_angle(a, b, t)));
+            }
+        }
+    }
+
+    #[test]
+    fn decayed_t_test() {
+        assert_eq!(decayed_t(0.5, 1.0), 0.5);
+        assert_eq!(decayed_t(3.0, 0.0), 3.0);
+
+        // Slows, never passing 1 + 1 / decay
+        let mut last = decayed_t(1.0, 2.0);
+        let mut last_step = f64::INFINITY;
+        for i in 1..50 {
+            let t = decayed_t(1.0 + i as f64 * 0.1, 2.0);
+            assert!(t > last && t - last < last_step && t < 1.5);
+            last_step = t - last;
+            last = t;
+        }
+    }
 }
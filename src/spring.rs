@@ -0,0 +1,139 @@
+use crate::{Snapshot, SnapshotStore};
+
+/// Arithmetic on a snapshot's values, required to step a spring.
+pub trait SpringSnapshot: Snapshot {
+    fn add(&self, other: &Self) -> Self;
+
+    fn sub(&self, other: &Self) -> Self;
+
+    fn scale(&self, scalar: f64) -> Self;
+}
+
+/// An alternative to `Playback` which follows the newest snapshot in the
+/// buffer with a spring-damper, rather than playing back buffered
+/// snapshots some periods behind. Trades smoothness under jitter for zero
+/// buffering latency.
+pub struct SpringPlayback<T> {
+    /// How strongly the spring pulls toward the newest snapshot
+    pub stiffness: f64,
+
+    /// How strongly the spring resists velocity. Critically damped at
+    /// `2 * sqrt(stiffness)`.
+    pub damping: f64,
+
+    position: Option<T>,
+    velocity: Option<T>,
+}
+
+impl<T: SpringSnapshot> SpringPlayback<T> {
+    pub fn new(stiffness: f64, damping: f64) -> Self {
+        Self {
+            stiffness,
+            damping,
+
+            position: None,
+            velocity: None,
+        }
+    }
+
+    /// A spring which approaches its target as fast as possible without
+    /// overshooting.
+    pub fn critically_damped(stiffness: f64) -> Self {
+        Self::new(stiffness, 2.0 * stiffness.sqrt())
+    }
+
+    /// Step the spring toward the newest snapshot by passing in how much
+    /// time has passed since the last step (seconds).
+    pub fn step(&mut self, delta_time: f64, buf: &impl SnapshotStore<T>) -> Option<T> {
+        let target = buf.front()?;
+
+        let (Some(position), Some(velocity)) = (&self.position, &self.velocity) else {
+            // Start at rest on the first snapshot
+            self.velocity = Some(target.scale(0.0));
+            self.position = Some(target.clone());
+            return self.position.clone();
+        };
+
+        // Implicit Euler, which is stable for any delta time and doesn't
+        // overshoot when critically damped
+        let dt = delta_time;
+        let velocity = velocity
+            .add(&target.sub(position).scale(dt * self.stiffness))
+            .scale(1.0 / (1.0 + dt * self.damping + dt * dt * self.stiffness));
+        let position = position.add(&velocity.scale(dt));
+
+        self.velocity = Some(velocity);
+        self.position = Some(position);
+        self.position.clone()
+    }
+
+    /// Snap to the newest snapshot on the next step
+    pub fn reset(&mut self) {
+        self.position = None;
+        self.velocity = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{SNAPSHOT_SETTINGS_DEFAULT, Snapshot, SpringPlayback, SpringSnapshot};
+
+    #[derive(Clone, Debug)]
+    struct Position {
+        time: f64,
+        x: f64,
+    }
+
+    impl Snapshot for Position {
+        fn interpolate(_: f64, _: &Self, to: &Self) -> Self {
+            to.clone()
+        }
+
+        fn remote_time(&self) -> f64 {
+            self.time
+        }
+    }
+
+    impl SpringSnapshot for Position {
+        fn add(&self, other: &Self) -> Self {
+            Position {
+                time: self.time,
+                x: self.x + other.x,
+            }
+        }
+
+        fn sub(&self, other: &Self) -> Self {
+            Position {
+                time: self.time,
+                x: self.x - other.x,
+            }
+        }
+
+        fn scale(&self, scalar: f64) -> Self {
+            Position {
+                time: self.time,
+                x: self.x * scalar,
+            }
+        }
+    }
+
+    #[test]
+    fn test_critically_damped() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut spring = SpringPlayback::critically_damped(50.0);
+
+        buf.insert_snapshot(Position { time: 0.0, x: 0.0 });
+        assert_eq!(spring.step(1.0 / 60.0, &buf).unwrap().x, 0.0);
+
+        buf.insert_snapshot(Position { time: 0.2, x: 10.0 });
+
+        let mut last = 0.0;
+        for _ in 0..300 {
+            let x = spring.step(1.0 / 60.0, &buf).unwrap().x;
+            assert!(x >= last, "{x} < {last}");
+            assert!(x <= 10.0, "overshot to {x}");
+            last = x;
+        }
+        assert!((last - 10.0).abs() < 1e-3, "{last}");
+    }
+}
@@ -0,0 +1,76 @@
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::ExponentialMovingAverage;
+
+/// A saved `Buffer`, for persisting a session and resuming it seamlessly.
+/// See `Buffer::save` and `Buffer::restore`.
+///
+/// Instants are saved as the time elapsed before saving, then re-anchored
+/// to the time of restoring, so that time spent saved doesn't count as the
+/// remote falling silent. Serializable with the `serde` feature.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BufferState<T> {
+    /// Newest first, each with the time since it was inserted, and its
+    /// time offset (seconds)
+    pub(crate) snapshots: Vec<(T, f64, f64)>,
+    pub(crate) buf_len: usize,
+
+    pub(crate) last_remote_time: f64,
+    /// The time since the newest snapshot arrived (seconds)
+    pub(crate) last_remote_age: f64,
+    pub(crate) last_remote_counter: u128,
+
+    pub(crate) remote_delta_time: ExponentialMovingAverage,
+    pub(crate) remote_delta_samples: usize,
+    pub(crate) remote_delta_spikes: usize,
+    pub(crate) recent_deltas: Vec<f64>,
+    pub(crate) frozen_offset: Option<(f64, f64)>,
+    pub(crate) time_epoch: f64,
+    pub(crate) monotonic_violations: usize,
+    pub(crate) rejected_snapshots: usize,
+}
+
+/// A saved `Playback`'s clock and measurements. Doesn't include any
+/// customization (e.g. `Playback::with_interpolate_fn`), which is kept by
+/// the playback restored into. See `Playback::save` and
+/// `Playback::restore`. Serializable with the `serde` feature.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PlaybackState {
+    pub(crate) remote_counter: u128,
+    pub(crate) seeded: bool,
+    pub(crate) time_epoch: f64,
+    pub(crate) playback_time: f64,
+    pub(crate) timescale: f64,
+    pub(crate) target_timescale: f64,
+    pub(crate) timescale_dt: f64,
+    pub(crate) segment_period: f64,
+    pub(crate) last_played_time: Option<f64>,
+
+    pub(crate) catchup_time: ExponentialMovingAverage,
+    pub(crate) catchup_confidence: ExponentialMovingAverage,
+    pub(crate) db_extrapolating_ema: ExponentialMovingAverage,
+    pub(crate) db_clamping_ema: ExponentialMovingAverage,
+    pub(crate) db_scaling_ema: ExponentialMovingAverage,
+    pub(crate) db_delta_clamping_ema: ExponentialMovingAverage,
+    pub(crate) dwell_time: ExponentialMovingAverage,
+    pub(crate) frame_time: ExponentialMovingAverage,
+}
+
+/// The time elapsed since `instant` as of `anchor` (seconds)
+pub(crate) fn age(anchor: Instant, instant: Instant) -> f64 {
+    anchor.saturating_duration_since(instant).as_secs_f64()
+}
+
+/// The instant `age` seconds before `anchor`, or `anchor` itself should
+/// that precede the platform's earliest instant
+pub(crate) fn reanchor(anchor: Instant, age: f64) -> Instant {
+    Duration::try_from_secs_f64(age)
+        .ok()
+        .and_then(|age| anchor.checked_sub(age))
+        .unwrap_or(anchor)
+}
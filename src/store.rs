@@ -0,0 +1,80 @@
+use std::time::Instant;
+
+use crate::{Settings, Snapshot};
+
+/// Storage of snapshots which a `Playback` can play back from, allowing
+/// callers to reuse their own storage rather than copying into a `Buffer`.
+///
+/// Snapshots must be ordered newest first, without duplicate remote times.
+pub trait SnapshotStore<T: Snapshot> {
+    /// Iterate over the stored snapshots, newest first
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a T>
+    where
+        T: 'a;
+
+    /// The newest snapshot
+    fn front(&self) -> Option<&T> {
+        self.iter().next()
+    }
+
+    /// The oldest snapshot
+    fn back(&self) -> Option<&T> {
+        self.iter().last()
+    }
+
+    /// The snapshot `index` places behind the newest
+    fn get(&self, index: usize) -> Option<&T> {
+        self.iter().nth(index)
+    }
+
+    /// The remote time of a snapshot borrowed from the store (as by `iter`,
+    /// `front`, `back` or `get`), which the store may adjust, e.g.
+    /// `Buffer::insert_snapshot_with_offset` (seconds). Called for every
+    /// snapshot scanned, so should be cheap. By default
+    /// `Snapshot::remote_time`.
+    fn remote_time_of(&self, snapshot: &T) -> f64 {
+        snapshot.remote_time()
+    }
+
+    /// A value which changes whenever a new snapshot arrives. By default
+    /// derived from the newest snapshot's remote time.
+    fn arrival_counter(&self) -> u128 {
+        self.front().map_or(0, |snapshot| {
+            self.remote_time_of(snapshot).to_bits() as u128
+        })
+    }
+
+    /// Estimate the remote's current clock (seconds). By default the remote
+    /// time of the newest snapshot.
+    fn estimated_remote_now(&self) -> f64 {
+        self.front()
+            .map_or(0.0, |snapshot| self.remote_time_of(snapshot))
+    }
+
+    /// Estimate the remote's clock as of the local instant `now`, rather
+    /// than the current instant (seconds). By default the same as
+    /// `estimated_remote_now`.
+    fn estimated_remote_now_at(&self, _now: Instant) -> f64 {
+        self.estimated_remote_now()
+    }
+
+    /// How far behind the remote the playback should aim to be (seconds).
+    /// By default the configured playback offset.
+    fn playback_offset(&self, settings: &Settings) -> f64 {
+        settings.playback_offset() as f64
+    }
+
+    /// Remote times are measured relative to this epoch internally, so
+    /// that they stay near zero and keep their precision over long
+    /// sessions (seconds). Must be exactly representable, and change
+    /// rarely. Zero by default.
+    fn time_epoch(&self) -> f64 {
+        0.0
+    }
+
+    /// When the snapshot `index` places behind the newest was stored
+    /// locally, if known. Used to measure the dwell time.
+    fn inserted_at(&self, _index: usize) -> Option<Instant> {
+        None
+    }
+}
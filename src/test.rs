@@ -1,6 +1,9 @@
 #[cfg(test)]
 mod tests {
-    use crate::{SNAPSHOT_SETTINGS_DEFAULT, Snapshot};
+    use crate::{
+        ClampBound, CorrectionDirection, FrameSnapshot, SNAPSHOT_SETTINGS_DEFAULT, Settings,
+        Snapshot, SnapshotStore, TimeCollision, TimescaleController, lerp,
+    };
 
     #[derive(Copy, Clone, Debug)]
     struct TestSnapshot {
@@ -18,9 +21,55 @@ mod tests {
         }
     }
 
+    #[derive(Copy, Clone, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    struct ValueSnapshot {
+        time: f64,
+        value: f64,
+    }
+
+    impl Snapshot for ValueSnapshot {
+        fn interpolate(t: f64, from: &Self, to: &Self) -> Self {
+            ValueSnapshot {
+                time: lerp(from.time, to.time, t),
+                value: lerp(from.value, to.value, t),
+            }
+        }
+
+        fn remote_time(&self) -> f64 {
+            self.time
+        }
+    }
+
+    impl FrameSnapshot for ValueSnapshot {
+        type Frame = f64;
+
+        fn interpolate_in_frame(t: f64, from: &Self, to: &Self, offset: &f64) -> Self {
+            let mut output = ValueSnapshot::interpolate(t, from, to);
+            output.value += offset;
+            output
+        }
+    }
+
+    /// A custom `SnapshotStore`, stored oldest first
+    struct VecStore(Vec<ValueSnapshot>);
+
+    impl SnapshotStore<ValueSnapshot> for VecStore {
+        fn iter<'a>(&'a self) -> impl Iterator<Item = &'a ValueSnapshot>
+        where
+            ValueSnapshot: 'a,
+        {
+            self.0.iter().rev()
+        }
+    }
+
+    fn leak(settings: Settings) -> &'static Settings {
+        Box::leak(Box::new(settings))
+    }
+
     #[test]
     fn test_snapshot_insertion() {
-        let mut buf = crate::Buffer::new(&*SNAPSHOT_SETTINGS_DEFAULT);
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
         // let mut play = snapshot::Playback::new(&buf);
 
         let one = TestSnapshot {
@@ -47,8 +96,3247 @@ mod tests {
         buf.insert_snapshot(three);
 
         assert_eq!(
-            buf.buf.iter().map(|s| s.number).collect::<Vec<_>>(),
+            buf.iter().map(|s| s.number).collect::<Vec<_>>(),
             vec![4, 3, 2, 1]
         );
     }
+
+    #[test]
+    fn test_zero_offset() {
+        let settings = leak(Settings::low_latency());
+        let mut buf = crate::Buffer::new(settings);
+        let mut play = crate::Playback::new(&buf);
+
+        for i in 1..=50 {
+            let time = i as f64 * settings.period;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+
+            let out = play.step(settings.period, &buf).unwrap();
+            assert!(out.value.is_finite());
+            assert!(play.playback_time.is_finite());
+
+            if i > 5 {
+                // Tracks the newest snapshot within a period
+                assert!((out.value - time).abs() <= settings.period);
+            }
+        }
+    }
+
+    #[test]
+    fn test_eviction() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        for number in 1..=10 {
+            let evicted = buf.insert_snapshot(TestSnapshot {
+                time: number as f64,
+                number,
+            });
+            assert!(evicted.is_none());
+        }
+
+        let evicted = buf.insert_snapshot(TestSnapshot {
+            time: 11.0,
+            number: 11,
+        });
+        assert_eq!(evicted.map(|s| s.number), Some(1));
+
+        // Arrived too late to ever be interpolated
+        let evicted = buf.insert_snapshot(TestSnapshot {
+            time: 0.5,
+            number: 0,
+        });
+        assert_eq!(evicted.map(|s| s.number), Some(0));
+    }
+
+    #[test]
+    fn test_adaptive_ema_window() {
+        // Configured for 5 packets per second, but really sending 20
+        let settings = leak(Settings {
+            adaptive_ema_window: true,
+            ..Settings::default()
+        });
+        let mut buf = crate::Buffer::new(settings);
+        assert_eq!(buf.remote_delta_time.window().round(), 10.0);
+
+        for number in 0..200 {
+            buf.insert_snapshot(TestSnapshot {
+                time: number as f64 * 0.05,
+                number,
+            });
+        }
+
+        assert_eq!(buf.remote_delta_time.window().round(), 40.0);
+    }
+
+    #[test]
+    fn test_last_advance() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+
+        buf.insert_snapshot(TestSnapshot {
+            time: 99.8,
+            number: 1,
+        });
+        buf.insert_snapshot(TestSnapshot {
+            time: 100.0,
+            number: 2,
+        });
+
+        // The playback time is clamped up towards the remote time
+        let delta_time = 1.0 / 60.0;
+        let timescale = play.timescale;
+        play.step(delta_time, &buf);
+
+        assert_eq!(play.last_advance(), play.playback_time);
+        assert!(play.last_advance() > delta_time * timescale);
+
+        // Unclamped, the playback time advances with the timescale
+        let timescale = play.timescale;
+        play.step(delta_time, &buf);
+        assert!((play.last_advance() - delta_time * timescale).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sample() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+        play.step(1.0 / 60.0, &buf);
+
+        let playback_time = play.playback_time;
+        let catchup_time = play.catchup_time.value;
+        let db_extrapolating = play.db_extrapolating_ema.value;
+
+        let sampled = play.sample(1.23, &buf).unwrap();
+        assert!((sampled.value - 1.23).abs() < 1e-9);
+
+        assert_eq!(play.playback_time, playback_time);
+        assert_eq!(play.catchup_time.value, catchup_time);
+        assert_eq!(play.db_extrapolating_ema.value, db_extrapolating);
+
+        // Matches what step produces at the same playback time
+        let delta_time = 1.0 / 60.0;
+        let sampled = play
+            .sample(play.playback_time + delta_time * play.timescale, &buf)
+            .unwrap();
+        let stepped = play.step(delta_time, &buf).unwrap();
+        assert_eq!(sampled.value, stepped.value);
+    }
+
+    #[test]
+    fn test_non_uniform_thresholds() {
+        let settings = &SNAPSHOT_SETTINGS_DEFAULT;
+        let mut buf = crate::Buffer::new(settings);
+        let mut play = crate::Playback::new(&buf);
+
+        assert!((play.fast_threshold() - settings.fast_threshold() as f64).abs() < 1e-6);
+        assert!((play.slow_threshold() - settings.slow_threshold() as f64).abs() < 1e-6);
+
+        // The remote throttles to sending every 0.5s, rather than every 0.2s
+        for (number, time) in [0.0, 0.2, 0.4, 0.9, 1.4, 1.9].into_iter().enumerate() {
+            buf.insert_snapshot(TestSnapshot { time, number });
+        }
+        play.playback_time = 1.5;
+        play.step(1.0 / 60.0, &buf);
+
+        assert!((play.fast_threshold() - 0.5 * settings.playback_fast_periods as f64).abs() < 1e-9);
+        assert!((play.slow_threshold() - 0.5 * settings.playback_slow_periods as f64).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_interpolate_fn() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+
+        let default = crate::Playback::new(&buf);
+        let held = crate::Playback::new(&buf).with_interpolate_fn(|t, from, to| ValueSnapshot {
+            time: lerp(from.time, to.time, t),
+            value: from.value,
+        });
+        let doubled = crate::Playback::new(&buf).with_interpolate_fn(|t, from, to| ValueSnapshot {
+            time: lerp(from.time, to.time, t),
+            value: lerp(from.value, to.value, t) * 2.0,
+        });
+
+        assert!((default.sample(1.1, &buf).unwrap().value - 1.1).abs() < 1e-9);
+        assert!((held.sample(1.1, &buf).unwrap().value - 1.0).abs() < 1e-9);
+        assert!((doubled.sample(1.1, &buf).unwrap().value - 2.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_time_collision() {
+        for (on_time_collision, expected) in [
+            (TimeCollision::KeepExisting, 1),
+            (TimeCollision::ReplaceWithNew, 2),
+        ] {
+            let settings = leak(Settings {
+                on_time_collision,
+                ..Settings::default()
+            });
+            let mut buf = crate::Buffer::new(settings);
+
+            buf.insert_snapshot(TestSnapshot {
+                time: 10.0,
+                number: 1,
+            });
+            buf.insert_snapshot(TestSnapshot {
+                time: 10.0,
+                number: 2,
+            });
+
+            assert_eq!(
+                buf.iter().map(|s| s.number).collect::<Vec<_>>(),
+                vec![expected]
+            );
+        }
+    }
+
+    #[test]
+    fn test_handover() {
+        let mut old_buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut new_buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            old_buf.insert_snapshot(ValueSnapshot { time, value: 0.0 });
+            new_buf.insert_snapshot(ValueSnapshot {
+                time: 50.0 + time,
+                value: 100.0,
+            });
+        }
+
+        let delta_time = 1.0 / 60.0;
+        let mut play = crate::Playback::new(&old_buf);
+        for _ in 0..10 {
+            assert_eq!(play.step(delta_time, &old_buf).unwrap().value, 0.0);
+        }
+
+        play.handover(&new_buf, 2.0);
+
+        // Blends rather than jumping straight to the new stream
+        let first = play.step(delta_time, &new_buf).unwrap().value;
+        assert!(first > 0.0 && first < 10.0, "{first}");
+
+        let mut last = first;
+        for _ in 0..30 {
+            let value = play.step(delta_time, &new_buf).unwrap().value;
+            assert!(value >= last);
+            last = value;
+        }
+        assert_eq!(last, 100.0);
+    }
+
+    #[test]
+    fn test_clamp_hook() {
+        #[derive(Clone)]
+        struct Health {
+            time: f64,
+            health: f64,
+        }
+
+        impl Snapshot for Health {
+            fn interpolate(t: f64, from: &Self, to: &Self) -> Self {
+                Health {
+                    time: lerp(from.time, to.time, t),
+                    health: lerp(from.health, to.health, t),
+                }
+            }
+
+            fn remote_time(&self) -> f64 {
+                self.time
+            }
+
+            fn clamp(&mut self) {
+                self.health = self.health.min(100.0);
+            }
+        }
+
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+        for i in 0..10 {
+            buf.insert_snapshot(Health {
+                time: i as f64 * 0.2,
+                health: 10.0 + i as f64 * 10.0,
+            });
+        }
+        play.step(1.0 / 60.0, &buf);
+
+        // Extrapolating beyond the newest snapshot would exceed 100
+        play.playback_time = 2.0;
+        assert_eq!(play.step(0.0, &buf).unwrap().health, 100.0);
+    }
+
+    #[test]
+    fn test_estimated_remote_now() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        buf.insert_snapshot(TestSnapshot {
+            time: 100.0,
+            number: 1,
+        });
+
+        std::thread::sleep(std::time::Duration::from_millis(50));
+
+        let now = buf.estimated_remote_now();
+        assert!(now >= 100.05, "{now}");
+        assert!(now < 100.5, "{now}");
+    }
+
+    #[test]
+    fn test_insertion_within_capacity() {
+        // At a fixed buffer length, the room allocated in `new` suffices
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let capacity = buf.buf.capacity();
+
+        for number in 0..100 {
+            buf.insert_snapshot(TestSnapshot {
+                time: number as f64,
+                number,
+            });
+            assert_eq!(buf.buf.capacity(), capacity);
+        }
+    }
+
+    #[cfg(feature = "allocator_api")]
+    #[test]
+    fn test_bump_allocator() {
+        use std::{
+            alloc::{AllocError, Allocator, Layout},
+            cell::{Cell, UnsafeCell},
+            ptr::NonNull,
+        };
+
+        struct BumpAllocator {
+            arena: UnsafeCell<[u8; 1 << 16]>,
+            used: Cell<usize>,
+        }
+
+        unsafe impl Allocator for &BumpAllocator {
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+                let base = self.arena.get() as *mut u8;
+                let start = (base as usize + self.used.get()).next_multiple_of(layout.align())
+                    - base as usize;
+                let end = start + layout.size();
+                if end > size_of::<[u8; 1 << 16]>() {
+                    return Err(AllocError);
+                }
+                self.used.set(end);
+
+                let ptr = NonNull::new(base.wrapping_add(start)).ok_or(AllocError)?;
+                Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+            }
+
+            // Freed all at once when the arena is dropped
+            unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {}
+        }
+
+        let bump = BumpAllocator {
+            arena: UnsafeCell::new([0; 1 << 16]),
+            used: Cell::new(0),
+        };
+
+        let mut buf = crate::Buffer::new_in(&SNAPSHOT_SETTINGS_DEFAULT, &bump);
+        let used = bump.used.get();
+        assert!(used > 0);
+
+        for number in 1..=100 {
+            buf.insert_snapshot(TestSnapshot {
+                time: number as f64,
+                number,
+            });
+        }
+
+        // Every snapshot went into the arena allocated in `new_in`
+        assert_eq!(bump.used.get(), used);
+        assert_eq!(buf.latest().unwrap().number, 100);
+
+        let mut play = crate::Playback::new(&buf);
+        assert!(play.step(0.01, &buf).is_some());
+    }
+
+    #[test]
+    fn test_would_extrapolate() {
+        let delta_time = 1.0 / 60.0;
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+
+        // Healthy, a period behind the newest snapshot
+        play.playback_time = 1.6;
+        assert!(!play.would_extrapolate(delta_time, &buf));
+        assert_eq!(play.playback_time, 1.6);
+
+        // Starved, about to pass the newest snapshot
+        play.playback_time = 1.8 - delta_time / 2.0;
+        assert!(play.would_extrapolate(delta_time, &buf));
+
+        // Half a period behind it, only about to pass it when sampling half
+        // a period ahead
+        play.playback_time = 1.7 - delta_time / 2.0;
+        assert!(!play.would_extrapolate(delta_time, &buf));
+
+        let settings = leak(Settings {
+            lookahead_periods: 0.5,
+            ..Settings::default()
+        });
+        let mut buf = crate::Buffer::new(settings);
+        let mut play = crate::Playback::new(&buf);
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+        play.playback_time = 1.7 - delta_time / 2.0;
+        assert!(play.would_extrapolate(delta_time, &buf));
+    }
+
+    #[test]
+    fn test_prune_before() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let play = crate::Playback::new(&buf);
+        for number in 0..10 {
+            buf.insert_snapshot(TestSnapshot {
+                time: number as f64,
+                number,
+            });
+        }
+
+        assert_eq!(buf.prune_before(5.5), 5);
+        assert_eq!(
+            buf.iter().map(|s| s.number).collect::<Vec<_>>(),
+            vec![9, 8, 7, 6, 5]
+        );
+
+        // The bracketing pair survives
+        assert_eq!(play.sample(5.5, &buf).unwrap().number, 6);
+
+        // Already pruned
+        assert_eq!(buf.prune_before(5.5), 0);
+
+        // Always keeps two snapshots
+        assert_eq!(buf.prune_before(100.0), 3);
+        assert_eq!(buf.iter().map(|s| s.number).collect::<Vec<_>>(), vec![9, 8]);
+    }
+
+    #[test]
+    fn test_first_snapshot_seeding() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+
+        assert!(play.step(1.0 / 60.0, &buf).is_none());
+
+        buf.insert_snapshot(TestSnapshot {
+            time: 1000.0,
+            number: 1,
+        });
+        play.step(1.0 / 60.0, &buf);
+
+        let target = 1000.0 - buf.dynamic_playback_offset();
+        assert!(
+            (play.playback_time - target).abs() < 0.05,
+            "{}",
+            play.playback_time
+        );
+    }
+
+    #[test]
+    fn test_lookahead() {
+        let lookahead_settings = leak(Settings {
+            lookahead_periods: 0.5,
+            ..Settings::default()
+        });
+
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+        let mut lookahead_buf = crate::Buffer::new(lookahead_settings);
+        let mut lookahead_play = crate::Playback::new(&lookahead_buf);
+
+        for i in 0..60 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+            lookahead_buf.insert_snapshot(ValueSnapshot { time, value: time });
+
+            let value = play.step(0.2, &buf).unwrap().value;
+            let lookahead_value = lookahead_play.step(0.2, &lookahead_buf).unwrap().value;
+
+            if i > 10 {
+                assert!((lookahead_value - value - 0.1).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_step_in_frame() {
+        /// Stood still on a platform which moves non-linearly
+        #[derive(Clone)]
+        struct Rider {
+            time: f64,
+            world_x: f64,
+            local_x: f64,
+        }
+
+        impl Snapshot for Rider {
+            fn interpolate(t: f64, from: &Self, to: &Self) -> Self {
+                Rider {
+                    time: lerp(from.time, to.time, t),
+                    world_x: lerp(from.world_x, to.world_x, t),
+                    local_x: lerp(from.local_x, to.local_x, t),
+                }
+            }
+
+            fn remote_time(&self) -> f64 {
+                self.time
+            }
+        }
+
+        impl FrameSnapshot for Rider {
+            /// The platform's position
+            type Frame = f64;
+
+            fn interpolate_in_frame(t: f64, from: &Self, to: &Self, platform_x: &f64) -> Self {
+                let local_x = lerp(from.local_x, to.local_x, t);
+                Rider {
+                    time: lerp(from.time, to.time, t),
+                    world_x: platform_x + local_x,
+                    local_x,
+                }
+            }
+        }
+
+        let platform_x = |time: f64| (time * 4.0).sin() * 5.0;
+
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(Rider {
+                time,
+                world_x: platform_x(time) + 1.0,
+                local_x: 1.0,
+            });
+        }
+
+        let delta_time = 1.0 / 60.0;
+        let mut world_play = crate::Playback::new(&buf);
+        let mut frame_play = crate::Playback::new(&buf);
+        world_play.step(delta_time, &buf);
+        frame_play.step(delta_time, &buf);
+
+        let mut world_error = 0.0;
+        let mut frame_error = 0.0;
+        for _ in 0..20 {
+            let output = world_play.step(delta_time, &buf).unwrap();
+            world_error += (output.world_x - platform_x(world_play.playback_time) - 1.0).abs();
+
+            let playback_time = frame_play.playback_time + delta_time * frame_play.timescale;
+            let output = frame_play
+                .step_in_frame(delta_time, &buf, &platform_x(playback_time))
+                .unwrap();
+            frame_error += (output.world_x - platform_x(frame_play.playback_time) - 1.0).abs();
+        }
+
+        assert!(
+            frame_error < world_error * 0.01,
+            "{frame_error} {world_error}"
+        );
+    }
+
+    #[test]
+    fn test_dwell_time() {
+        use std::time::{Duration, Instant};
+
+        let settings = leak(Settings {
+            period: 0.02,
+            dynamic_playback_time: false,
+            ..Settings::default()
+        });
+        let mut buf = crate::Buffer::new(settings);
+        let mut play = crate::Playback::new(&buf);
+
+        // Simulated arrivals every period and frames every 2ms
+        let start = Instant::now();
+        let at = |seconds: f64| start + Duration::from_secs_f64(seconds);
+        let mut last_send = f64::NEG_INFINITY;
+        for frame in 0..300 {
+            let now = frame as f64 * 0.002;
+            if now - last_send >= settings.period - 1e-9 {
+                last_send = now;
+                buf.insert_snapshot_received_at(
+                    ValueSnapshot {
+                        time: now,
+                        value: now,
+                    },
+                    at(now),
+                );
+            }
+
+            play.step_instant(at(now), &buf);
+        }
+
+        // Snapshots dwell for roughly the playback offset
+        let dwell_time = play.dwell_time.value.unwrap();
+        let playback_offset = settings.playback_offset() as f64;
+        assert!((dwell_time - playback_offset).abs() < 0.005, "{dwell_time}");
+    }
+
+    #[test]
+    fn test_version_boundary() {
+        #[derive(Clone)]
+        struct Versioned {
+            time: f64,
+            value: f64,
+            version: u32,
+        }
+
+        impl Snapshot for Versioned {
+            fn interpolate(t: f64, from: &Self, to: &Self) -> Self {
+                Versioned {
+                    time: lerp(from.time, to.time, t),
+                    value: lerp(from.value, to.value, t),
+                    version: to.version,
+                }
+            }
+
+            fn remote_time(&self) -> f64 {
+                self.time
+            }
+
+            fn version(&self) -> u32 {
+                self.version
+            }
+        }
+
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let play = crate::Playback::new(&buf);
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(Versioned {
+                time,
+                value: time,
+                version: if i < 5 { 1 } else { 2 },
+            });
+        }
+
+        // Within a version
+        assert!((play.sample(0.3, &buf).unwrap().value - 0.3).abs() < 1e-9);
+        assert!((play.sample(1.3, &buf).unwrap().value - 1.3).abs() < 1e-9);
+
+        // Across the version boundary snaps to the newer snapshot
+        let output = play.sample(0.9, &buf).unwrap();
+        assert_eq!(output.version, 2);
+        assert_eq!(output.value, 1.0);
+    }
+
+    #[test]
+    fn test_step_many() {
+        let settings = &SNAPSHOT_SETTINGS_DEFAULT;
+        let mut stores = (0..4).map(|_| VecStore(Vec::new())).collect::<Vec<_>>();
+        let mut batched = (0..4)
+            .map(|_| crate::Playback::with_settings(settings))
+            .collect::<Vec<_>>();
+        let mut individual = (0..4)
+            .map(|_| crate::Playback::with_settings(settings))
+            .collect::<Vec<_>>();
+
+        for frame in 0..300 {
+            if frame % 12 == 0 {
+                let time = frame as f64 / 60.0;
+                for (entity, store) in stores.iter_mut().enumerate() {
+                    store.0.push(ValueSnapshot {
+                        time,
+                        value: time * entity as f64,
+                    });
+                }
+            }
+
+            let outputs = crate::Playback::step_many(
+                &mut batched.iter_mut().collect::<Vec<_>>(),
+                1.0 / 60.0,
+                &stores.iter().collect::<Vec<_>>(),
+            );
+
+            // Identical to stepping each individually
+            for ((output, play), store) in outputs.into_iter().zip(&mut individual).zip(&stores) {
+                let expected = play.step(1.0 / 60.0, store);
+                let (output, expected) = (output.unwrap(), expected.unwrap());
+                assert_eq!(output.time, expected.time);
+                assert_eq!(output.value, expected.value);
+            }
+        }
+        for (batched, individual) in batched.iter().zip(&individual) {
+            assert_eq!(batched.playback_time, individual.playback_time);
+            assert_eq!(batched.timescale, individual.timescale);
+        }
+    }
+
+    #[test]
+    fn test_error_signal() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+        play.step(0.0, &buf);
+        play.playback_time = 1.0;
+
+        let target = 1.8 - buf.dynamic_playback_offset();
+        assert!((play.target_playback_time(&buf) - target).abs() < 1e-3);
+        assert!((play.instantaneous_error(&buf) - (target - 1.0)).abs() < 1e-3);
+
+        // Matches the catchup time sampled by step when a packet arrives
+        buf.insert_snapshot(ValueSnapshot {
+            time: 2.0,
+            value: 2.0,
+        });
+        play.catchup_time.reset();
+        play.step(0.0, &buf);
+        let catchup_time = play.catchup_time.value.unwrap();
+        assert!(catchup_time > 0.0);
+        assert!((play.instantaneous_error(&buf) - catchup_time).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_timescale_controller() {
+        struct Proportional {
+            gain: f64,
+        }
+
+        impl TimescaleController for Proportional {
+            fn compute(&mut self, error: f64, _: f64) -> f64 {
+                (1.0 + self.gain * error).clamp(0.5, 1.5)
+            }
+        }
+
+        let settings = leak(Settings {
+            dynamic_playback_time: false,
+            ..Settings::default()
+        });
+        let mut buf = crate::Buffer::new(settings);
+        let mut play =
+            crate::Playback::new(&buf).with_timescale_controller(Proportional { gain: 2.0 });
+
+        let delta_time = 1.0 / 60.0;
+        let mut now = 0.0;
+        let mut next_send = 0.0;
+        for frame in 0..600 {
+            if now >= next_send {
+                buf.insert_snapshot(ValueSnapshot {
+                    time: next_send,
+                    value: next_send,
+                });
+                next_send += settings.period;
+            }
+
+            play.step(delta_time, &buf);
+            if frame == 0 {
+                // Start behind, within the clamp
+                play.playback_time -= 0.15;
+            }
+            now += delta_time;
+        }
+
+        let catchup_time = play.catchup_time.value.unwrap();
+        assert!(catchup_time.abs() < 0.02, "{catchup_time}");
+    }
+
+    #[test]
+    fn test_negative_delta_time() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+        play.step(1.0 / 60.0, &buf);
+
+        let playback_time = play.playback_time;
+        assert!(play.step(-10.0, &buf).is_some());
+        assert_eq!(play.playback_time, playback_time);
+        assert!(play.step(f64::NAN, &buf).is_some());
+        assert_eq!(play.playback_time, playback_time);
+        assert!(play.db_delta_clamping_ema.value.unwrap() > 0.0);
+
+        // Capped at a sane maximum
+        play.step(1000.0, &buf);
+        assert!(play.playback_time - playback_time <= 5.0 * play.timescale);
+    }
+
+    #[test]
+    fn test_fill_ratio() {
+        // Holds 10 snapshots
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        assert_eq!(buf.fill_ratio(), 0.0);
+
+        for number in 0..5 {
+            buf.insert_snapshot(TestSnapshot {
+                time: number as f64,
+                number,
+            });
+        }
+        assert_eq!(buf.fill_ratio(), 0.5);
+
+        for number in 5..20 {
+            buf.insert_snapshot(TestSnapshot {
+                time: number as f64,
+                number,
+            });
+        }
+        assert_eq!(buf.fill_ratio(), 1.0);
+    }
+
+    #[test]
+    fn test_catchup_dead_zone() {
+        let settings = leak(Settings {
+            playback_fast_periods: 0.05,
+            playback_slow_periods: -0.05,
+            ..Settings::default()
+        });
+        let dead_zone_settings = leak(Settings {
+            catchup_dead_zone_periods: 0.2,
+            ..settings.clone()
+        });
+
+        let play = crate::Playback::<TestSnapshot>::new(&crate::Buffer::new(settings));
+        let dead_zone_play =
+            crate::Playback::<TestSnapshot>::new(&crate::Buffer::new(dead_zone_settings));
+
+        // Small catchup times, a fraction of a period
+        let catchup_times = [0.02, -0.02, 0.03, -0.01];
+        let scaled = catchup_times
+            .iter()
+            .filter(|&&catchup_time| play.timescale(catchup_time) != 1.0)
+            .count();
+        assert!(scaled > catchup_times.len() / 2);
+        for catchup_time in catchup_times {
+            assert_eq!(dead_zone_play.timescale(catchup_time), 1.0);
+        }
+
+        // Beyond the dead zone still scales
+        assert_ne!(dead_zone_play.timescale(0.1), 1.0);
+    }
+
+    #[test]
+    fn test_custom_store() {
+        let settings = &SNAPSHOT_SETTINGS_DEFAULT;
+        let mut store = VecStore(Vec::new());
+        let mut play = crate::Playback::with_settings(settings);
+
+        let delta_time = 1.0 / 60.0;
+        let mut now = 0.0;
+        let mut next_send = 0.0;
+        for frame in 0..300 {
+            if now >= next_send {
+                store.0.push(ValueSnapshot {
+                    time: next_send,
+                    value: next_send,
+                });
+                next_send += settings.period;
+            }
+
+            let output = play.step(delta_time, &store).unwrap();
+            if frame > 60 {
+                assert!((output.value - play.playback_time).abs() < 1e-9);
+                assert!(play.playback_time < store.front().unwrap().time + delta_time);
+            }
+            now += delta_time;
+        }
+        assert!(play.playback_time > 4.0);
+    }
+
+    #[test]
+    fn test_jitter_spike_reset() {
+        let spike_reset_settings = leak(Settings {
+            jitter_spike_reset: Some(4.0),
+            ..Settings::default()
+        });
+
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut spike_reset_buf = crate::Buffer::new(spike_reset_settings);
+
+        let mut time = 0.0;
+        let mut insert = |time: f64| {
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+            spike_reset_buf.insert_snapshot(ValueSnapshot { time, value: time });
+            (
+                buf.dynamic_playback_offset(),
+                spike_reset_buf.dynamic_playback_offset(),
+            )
+        };
+
+        // Slightly jittery
+        let mut before = (0.0, 0.0);
+        for i in 0..50 {
+            time += if i % 2 == 0 { 0.19 } else { 0.21 };
+            before = insert(time);
+        }
+
+        // A single massive spike, followed by a recovered network
+        time += 3.0;
+        insert(time);
+        let mut after = (0.0, 0.0);
+        for i in 0..5 {
+            time += if i % 2 == 0 { 0.19 } else { 0.21 };
+            after = insert(time);
+        }
+
+        assert!(after.0 > before.0 * 1.5, "{after:?} {before:?}");
+        assert!(after.1 < before.1 * 1.1, "{after:?} {before:?}");
+    }
+
+    #[test]
+    fn test_last_quality() {
+        use std::time::{Duration, Instant};
+
+        // Simulated arrivals and frames, independent of the wall clock
+        let run = |dropped: &dyn Fn(usize) -> bool| {
+            let start = Instant::now();
+            let at = |seconds: f64| start + Duration::from_secs_f64(seconds);
+
+            let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+            let mut play = crate::Playback::new(&buf);
+            for i in 0..100 {
+                let time = i as f64 * 0.2;
+                if !dropped(i) {
+                    buf.insert_snapshot_received_at(ValueSnapshot { time, value: time }, at(time));
+                }
+                for frame in 0..12 {
+                    play.step_instant(at(time + frame as f64 / 60.0), &buf);
+                }
+            }
+            play.last_quality()
+        };
+
+        let perfect = run(&|_| false);
+        let lossy = run(&|i| i % 10 >= 4);
+
+        // The debug EMAs are sampled on arrival, and the surviving
+        // snapshots arrive on time, so loss only registers through the
+        // corrections made as the jitter measurement widens the offset
+        assert!(perfect > 0.99, "{perfect}");
+        assert!(lossy < perfect - 0.005, "{lossy} {perfect}");
+    }
+
+    #[test]
+    fn test_quadratic_interpolation() {
+        #[derive(Copy, Clone, Debug)]
+        struct CurvedSnapshot {
+            time: f64,
+            value: f64,
+        }
+
+        impl Snapshot for CurvedSnapshot {
+            fn interpolate(t: f64, from: &Self, to: &Self) -> Self {
+                CurvedSnapshot {
+                    time: lerp(from.time, to.time, t),
+                    value: lerp(from.value, to.value, t),
+                }
+            }
+
+            fn interpolate_quadratic(t: f64, prev: &Self, from: &Self, to: &Self) -> Self {
+                CurvedSnapshot {
+                    time: lerp(from.time, to.time, t),
+                    value: crate::quad_bezier(prev.value, from.value, to.value, t),
+                }
+            }
+
+            fn remote_time(&self) -> f64 {
+                self.time
+            }
+        }
+
+        // Accelerating from rest
+        let position = |time: f64| time * time;
+
+        let mut linear_buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut quadratic_buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            linear_buf.insert_snapshot(ValueSnapshot {
+                time,
+                value: position(time),
+            });
+            quadratic_buf.insert_snapshot(CurvedSnapshot {
+                time,
+                value: position(time),
+            });
+        }
+
+        let linear_play = crate::Playback::new(&linear_buf);
+        let quadratic_play = crate::Playback::new(&quadratic_buf);
+
+        let mut linear_error = 0.0;
+        let mut quadratic_error = 0.0;
+        for i in 0..40 {
+            let time = 0.5 + i as f64 * 0.025;
+            let linear = linear_play.sample(time, &linear_buf).unwrap();
+            let quadratic = quadratic_play.sample(time, &quadratic_buf).unwrap();
+            linear_error += (linear.value - position(time)).abs();
+            quadratic_error += (quadratic.value - position(time)).abs();
+        }
+
+        assert!(
+            quadratic_error < linear_error * 0.1,
+            "{quadratic_error} {linear_error}"
+        );
+    }
+
+    #[test]
+    fn test_single_snapshot() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+        buf.insert_snapshot(ValueSnapshot {
+            time: 10.0,
+            value: 5.0,
+        });
+
+        for _ in 0..60 {
+            let output = play.step(1.0 / 60.0, &buf).unwrap();
+            assert_eq!(output.value, 5.0);
+            assert!((play.playback_time - play.target_playback_time(&buf)).abs() < 1e-3);
+        }
+
+        assert_eq!(play.db_extrapolating_ema.value, Some(0.0));
+        assert_eq!(play.db_clamping_ema.value, Some(0.0));
+
+        // Playback resumes as usual from the target time once a second arrives
+        buf.insert_snapshot(ValueSnapshot {
+            time: 10.2,
+            value: 6.0,
+        });
+        play.step(1.0 / 60.0, &buf).unwrap();
+        assert_eq!(play.db_clamping_ema.value, Some(0.0));
+    }
+
+    #[test]
+    fn test_slow_only_correction() {
+        let settings = leak(Settings {
+            correction_direction: CorrectionDirection::SlowOnly,
+            ..Settings::default()
+        });
+
+        let mut buf = crate::Buffer::new(settings);
+        let mut play = crate::Playback::new(&buf);
+
+        // Far behind and far ahead
+        assert_eq!(play.timescale(-10.0), settings.playback_slow_speed as f64);
+        assert_eq!(play.timescale(10.0), 1.0);
+
+        for i in 0..50 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+            play.step(1.0 / 60.0, &buf);
+
+            // Fall well behind the target between packets
+            play.playback_time -= 0.15;
+            assert!(play.timescale <= 1.0);
+        }
+    }
+
+    #[test]
+    fn test_sample_at_detailed() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        assert!(buf.sample_at_detailed(0.0).is_none());
+
+        for i in 0..5 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot {
+                time,
+                value: i as f64 * 10.0,
+            });
+        }
+
+        // A quarter of the way between 0.2 (position 3) and 0.4 (position 2)
+        let (output, t, from, to) = buf.sample_at_detailed(0.25).unwrap();
+        assert!((t - 0.25).abs() < 1e-9);
+        assert_eq!((from, to), (3, 2));
+        assert!((output.value - 12.5).abs() < 1e-9);
+
+        // Extrapolating beyond the newest two
+        let (_, t, from, to) = buf.sample_at_detailed(0.9).unwrap();
+        assert!((t - 1.5).abs() < 1e-9);
+        assert_eq!((from, to), (1, 0));
+
+        // Older than every snapshot
+        assert!(buf.sample_at_detailed(-1.0).is_none());
+    }
+
+    #[test]
+    fn test_max_buffer_bytes() {
+        #[derive(Clone, Debug)]
+        struct SizedSnapshot {
+            time: f64,
+            data: Vec<u8>,
+        }
+
+        impl Snapshot for SizedSnapshot {
+            fn interpolate(_: f64, _: &Self, to: &Self) -> Self {
+                to.clone()
+            }
+
+            fn remote_time(&self) -> f64 {
+                self.time
+            }
+
+            fn approx_size(&self) -> usize {
+                std::mem::size_of::<Self>() + self.data.len()
+            }
+        }
+
+        let settings = leak(Settings {
+            max_buffer_bytes: Some(1000),
+            ..Settings::default()
+        });
+        let mut buf = crate::Buffer::new(settings);
+
+        let sizes = [100, 300, 50, 400, 200, 10, 600, 20, 2000, 30];
+        for (i, size) in sizes.into_iter().enumerate() {
+            let evicted = buf.insert_snapshot(SizedSnapshot {
+                time: i as f64 * 0.2,
+                data: vec![0; size],
+            });
+
+            if buf.iter().count() > 1 {
+                assert!(buf.approx_bytes() <= 1000, "{}", buf.approx_bytes());
+            }
+            // Only the oldest are evicted
+            if let Some(evicted) = evicted {
+                assert!(buf.iter().all(|s| s.time > evicted.time));
+            }
+        }
+
+        // Too large to fit alone, but the newest is always kept
+        assert_eq!(buf.latest().unwrap().data.len(), 30);
+    }
+
+    #[test]
+    fn test_last_clamp() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+        play.step(0.0, &buf);
+        assert_eq!(play.last_clamp(), None);
+
+        let clamp = SNAPSHOT_SETTINGS_DEFAULT.playback_clamp() as f64;
+        let mut time = 1.8;
+        let mut force = |play: &mut crate::Playback<ValueSnapshot>, offset: f64| {
+            time += 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+            play.playback_time = play.target_playback_time(&buf) + offset;
+            play.step(0.0, &buf);
+            play.last_clamp()
+        };
+
+        // Too far behind
+        let event = force(&mut play, -clamp - 0.5).unwrap();
+        assert_eq!(event.bound, ClampBound::Min);
+        assert!((event.amount - 0.5).abs() < 1e-3);
+
+        // Too far ahead
+        let event = force(&mut play, clamp + 0.25).unwrap();
+        assert_eq!(event.bound, ClampBound::Max);
+        assert!((event.amount - 0.25).abs() < 1e-3);
+
+        // Within the clamp
+        assert_eq!(force(&mut play, 0.0), None);
+
+        // Only reported for the step it was applied in
+        play.playback_time -= clamp + 0.5;
+        play.step(0.0, &buf);
+        assert_eq!(play.last_clamp(), None);
+    }
+
+    #[test]
+    fn test_easing_curve() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+
+        let linear = crate::Playback::new(&buf);
+        let eased = crate::Playback::new(&buf).with_easing_curve(crate::EasingCurve::new(vec![
+            (0.0, 0.0),
+            (0.5, 0.25),
+            (1.0, 1.0),
+        ]));
+
+        // Halfway between 1.0 and 1.2
+        assert!((linear.sample(1.1, &buf).unwrap().value - 1.1).abs() < 1e-9);
+        assert!((eased.sample(1.1, &buf).unwrap().value - 1.05).abs() < 1e-9);
+
+        // Snapshots themselves are unchanged
+        assert!((eased.sample(1.2, &buf).unwrap().value - 1.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_time_epoch() {
+        let run = |base: f64| {
+            let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+            let mut play = crate::Playback::new(&buf);
+            for i in 0..20 {
+                // Exactly representable, so only the playback's precision is measured
+                buf.insert_snapshot(ValueSnapshot {
+                    time: base + i as f64 * 0.25,
+                    value: i as f64 * 0.25,
+                });
+            }
+
+            play.step(0.0, &buf);
+            play.playback_time = base - play.time_epoch() + 3.0;
+
+            let mut max_error: f64 = 0.0;
+            for i in 1..=90 {
+                let output = play.step(1.0 / 60.0, &buf).unwrap();
+                let expected = 3.0 + i as f64 / 60.0;
+                max_error = max_error.max((output.value - expected).abs());
+            }
+            max_error
+        };
+
+        let small = run(0.0);
+        let large = run(1e9);
+        assert!(small < 1e-9, "{small}");
+        assert!(large < 1e-9, "{large}");
+    }
+
+    #[test]
+    fn test_timescale_slew() {
+        let settings = leak(Settings {
+            timescale_slew_per_second: Some(0.02),
+            ..Settings::default()
+        });
+
+        let mut buf = crate::Buffer::new(settings);
+        let mut play = crate::Playback::new(&buf);
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+        play.step(0.0, &buf);
+        assert_eq!(play.timescale, 1.0);
+
+        // Fall far behind, targetting the fast speed
+        buf.insert_snapshot(ValueSnapshot {
+            time: 2.0,
+            value: 2.0,
+        });
+        play.playback_time -= 0.5;
+        play.catchup_time.reset();
+
+        // 0.02 / 0.02 per second ramps over a second, 60 steps
+        let delta_time = 1.0 / 60.0;
+        let fast_speed = settings.playback_fast_speed as f64;
+        let mut steps = 0;
+        while play.timescale < fast_speed - 1e-9 {
+            let before = play.timescale;
+            play.step(delta_time, &buf);
+            assert!(play.timescale - before <= 0.02 * delta_time + 1e-9);
+            steps += 1;
+            assert!(steps <= 60);
+        }
+        assert_eq!(steps, 60);
+    }
+
+    #[test]
+    fn test_step_with_neighbors() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+
+        let mut play = crate::Playback::new(&buf);
+        let mut reference = crate::Playback::new(&buf);
+        play.step(0.0, &buf);
+        reference.step(0.0, &buf);
+        reference.playback_time = play.playback_time;
+        reference.timescale = play.timescale;
+
+        // Interpolating, then extrapolating beyond the newest
+        for _ in 0..30 {
+            let (output, from, to) = play.step_with_neighbors(1.0 / 60.0, &buf).unwrap();
+            let expected = reference.step(1.0 / 60.0, &buf).unwrap();
+            assert_eq!(output.value, expected.value);
+
+            if play.would_extrapolate(0.0, &buf) {
+                assert_eq!((from.time, to.time), (1.6, 1.8));
+            } else {
+                assert!(from.time < play.playback_time && play.playback_time <= to.time);
+                assert!((to.time - from.time - 0.2).abs() < 1e-9);
+            }
+
+            // The output lies along the returned segment
+            let t = (output.value - from.value) / (to.value - from.value);
+            assert!((lerp(from.time, to.time, t) - output.time).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_extrapolation_decay() {
+        #[derive(Copy, Clone, Debug)]
+        struct CoastingSnapshot {
+            time: f64,
+            x: f64,
+        }
+
+        impl Snapshot for CoastingSnapshot {
+            fn interpolate(t: f64, from: &Self, to: &Self) -> Self {
+                CoastingSnapshot {
+                    time: lerp(from.time, to.time, t),
+                    x: lerp(from.x, to.x, t),
+                }
+            }
+
+            fn extrapolate(from: &Self, to: &Self, t: f64, decay: f64) -> Self {
+                Self::interpolate(crate::decayed_t(t, decay), from, to)
+            }
+
+            fn remote_time(&self) -> f64 {
+                self.time
+            }
+        }
+
+        let settings = leak(Settings {
+            extrapolation_decay: 1.0,
+            ..Settings::default()
+        });
+
+        let mut buf = crate::Buffer::new(settings);
+        let mut play = crate::Playback::new(&buf);
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(CoastingSnapshot { time, x: time });
+        }
+        play.step(0.0, &buf);
+        play.playback_time = 1.8;
+
+        // A long gap, with each 50ms coasting less far than the last
+        let mut last_x = play.step(0.0, &buf).unwrap().x;
+        let mut last_displacement = f64::INFINITY;
+        for _ in 0..5 {
+            let mut x = last_x;
+            for _ in 0..3 {
+                x = play.step(1.0 / 60.0, &buf).unwrap().x;
+            }
+            let displacement = x - last_x;
+            assert!(displacement > 0.0 && displacement < last_displacement);
+            last_displacement = displacement;
+            last_x = x;
+        }
+        // Never beyond 1 / decay periods past the newest
+        assert!(last_x < 1.8 + 0.2 / 1.0);
+    }
+
+    #[test]
+    fn test_data_age() {
+        use std::time::{Duration, Instant};
+
+        let start = Instant::now();
+        let at = |seconds: f64| start + Duration::from_secs_f64(seconds);
+
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        buf.insert_snapshot_received_at(
+            ValueSnapshot {
+                time: 0.0,
+                value: 0.0,
+            },
+            at(0.0),
+        );
+        assert_eq!(buf.data_age_at(at(0.0)), 0.0);
+        assert!((buf.data_age_at(at(0.06)) - 0.06).abs() < 1e-9);
+
+        // Freshened by the next snapshot
+        buf.insert_snapshot_received_at(
+            ValueSnapshot {
+                time: 0.2,
+                value: 0.2,
+            },
+            at(0.5),
+        );
+        assert!((buf.data_age_at(at(0.56)) - 0.06).abs() < 1e-9);
+
+        // Never negative
+        assert_eq!(buf.data_age_at(at(0.0)), 0.0);
+    }
+
+    #[test]
+    fn test_max_extrapolation() {
+        let settings = leak(Settings {
+            max_extrapolation_periods: Some(0.5),
+            ..Settings::default()
+        });
+
+        let mut buf = crate::Buffer::new(settings);
+        let mut play = crate::Playback::new(&buf);
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+        play.step(0.0, &buf);
+
+        // A long gap
+        for _ in 0..120 {
+            play.step(1.0 / 60.0, &buf);
+            assert!(play.playback_time <= 1.8 + 0.1 + 1e-6);
+        }
+        assert!((play.playback_time - 1.9).abs() < 1e-6);
+        assert_eq!(play.last_advance(), 0.0);
+    }
+
+    #[test]
+    fn test_monotonic_violations() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        for i in 0..15 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+        assert_eq!(buf.monotonic_violations, 0);
+
+        // The remote clock jitters backward by a few ms
+        buf.insert_snapshot(ValueSnapshot {
+            time: 2.797,
+            value: 2.797,
+        });
+        assert_eq!(buf.monotonic_violations, 1);
+
+        let times = buf.iter().map(|s| s.time).collect::<Vec<_>>();
+        assert!(times.windows(2).all(|w| w[0] > w[1]));
+        assert_eq!(times[1], 2.797);
+
+        // Too late to be kept, or a duplicate, aren't counted
+        buf.insert_snapshot(ValueSnapshot {
+            time: 0.1,
+            value: 0.1,
+        });
+        buf.insert_snapshot(ValueSnapshot {
+            time: 2.6,
+            value: 2.6,
+        });
+        assert_eq!(buf.monotonic_violations, 1);
+    }
+
+    #[test]
+    fn test_step_on_change() {
+        #[derive(Copy, Clone, Debug)]
+        struct PositionSnapshot {
+            time: f64,
+            x: f64,
+        }
+
+        impl Snapshot for PositionSnapshot {
+            fn interpolate(t: f64, from: &Self, to: &Self) -> Self {
+                PositionSnapshot {
+                    time: lerp(from.time, to.time, t),
+                    x: lerp(from.x, to.x, t),
+                }
+            }
+
+            fn remote_time(&self) -> f64 {
+                self.time
+            }
+
+            fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+                (self.x - other.x).abs() <= epsilon
+            }
+        }
+
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+
+        // Moving, then static
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(PositionSnapshot {
+                time,
+                x: time.min(1.0),
+            });
+        }
+        play.step(0.0, &buf);
+        play.playback_time = 0.8;
+
+        let mut emitted = 0;
+        for _ in 0..60 {
+            if play.step_on_change(1.0 / 60.0, &buf, 1e-6).is_some() {
+                emitted += 1;
+            }
+        }
+        assert!(emitted > 0);
+
+        // Settled on the static trajectory
+        for _ in 0..10 {
+            assert!(play.step_on_change(1.0 / 60.0, &buf, 1e-6).is_none());
+        }
+    }
+
+    #[test]
+    fn test_step_reverse() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot {
+                time,
+                value: (time * 5.0).sin(),
+            });
+        }
+        play.step(0.0, &buf);
+        play.playback_time = 0.3;
+        play.timescale = 1.0;
+
+        let delta_time = 1.0 / 60.0;
+        let mut forward = vec![play.sample(play.playback_time, &buf).unwrap().value];
+        for _ in 0..60 {
+            forward.push(play.step(delta_time, &buf).unwrap().value);
+        }
+
+        // Retraces the same path backward
+        for expected in forward.iter().rev().skip(1) {
+            let output = play.step_reverse(delta_time, &buf).unwrap();
+            assert!((output.value - expected).abs() < 1e-6);
+            assert!((play.last_advance() + delta_time).abs() < 1e-9);
+        }
+        assert!((play.playback_time - 0.3).abs() < 1e-9);
+
+        // Never rewinds beyond the oldest snapshot
+        for _ in 0..60 {
+            play.step_reverse(delta_time, &buf);
+        }
+        assert_eq!(play.playback_time, 0.0);
+        assert_eq!(play.step_reverse(delta_time, &buf).unwrap().value, 0.0);
+    }
+
+    #[test]
+    fn test_parent_change() {
+        #[derive(Copy, Clone, Debug)]
+        struct AttachedSnapshot {
+            time: f64,
+            parent: u64,
+            local_x: f64,
+        }
+
+        impl Snapshot for AttachedSnapshot {
+            fn interpolate(t: f64, from: &Self, to: &Self) -> Self {
+                AttachedSnapshot {
+                    time: lerp(from.time, to.time, t),
+                    parent: to.parent,
+                    local_x: lerp(from.local_x, to.local_x, t),
+                }
+            }
+
+            fn remote_time(&self) -> f64 {
+                self.time
+            }
+
+            fn parent_id(&self) -> Option<u64> {
+                Some(self.parent)
+            }
+        }
+
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let play = crate::Playback::new(&buf);
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            // Handed from parent 1 to parent 2 at 1.0, at a different offset
+            let (parent, local_x) = if time < 0.9 {
+                (1, time)
+            } else {
+                (2, 10.0 + time)
+            };
+            buf.insert_snapshot(AttachedSnapshot {
+                time,
+                parent,
+                local_x,
+            });
+        }
+
+        // Blends within a parent
+        let output = play.sample(0.5, &buf).unwrap();
+        assert_eq!(output.parent, 1);
+        assert!((output.local_x - 0.5).abs() < 1e-9);
+
+        // Snaps across the change, never blending between the parents
+        for i in 1..20 {
+            let output = play.sample(0.8 + i as f64 * 0.01, &buf).unwrap();
+            assert_eq!(output.parent, 2);
+            assert_eq!(output.local_x, 11.0);
+        }
+
+        // Blends within the new parent
+        let output = play.sample(1.1, &buf).unwrap();
+        assert!((output.local_x - 11.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_auto_buffer() {
+        let settings = leak(Settings {
+            auto_buffer: true,
+            ..Settings::default()
+        });
+        let mut buf = crate::Buffer::new(settings);
+
+        let mut time = 0.0;
+        let mut insert = |buf: &mut crate::Buffer<ValueSnapshot>, deltas: [f64; 2]| {
+            for i in 0..100 {
+                time += deltas[i % 2];
+                buf.insert_snapshot(ValueSnapshot { time, value: time });
+                assert!(buf.iter().count() <= buf.max_len());
+            }
+            buf.max_len()
+        };
+
+        let calm = insert(&mut buf, [0.2, 0.2]);
+        let jittery = insert(&mut buf, [0.05, 0.55]);
+        let recovered = insert(&mut buf, [0.2, 0.2]);
+
+        // The offset of one period, plus the two period margin
+        assert_eq!(calm, 3);
+        assert!(jittery > calm, "{jittery} {calm}");
+        assert_eq!(recovered, calm);
+        assert_eq!(buf.iter().count(), calm);
+    }
+
+    #[test]
+    fn test_step_to() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot {
+                time,
+                value: (time * 3.0).sin(),
+            });
+        }
+
+        // One playback has been running its own catchup, the other hasn't
+        let mut a = crate::Playback::new(&buf);
+        let mut b = crate::Playback::new(&buf);
+        for _ in 0..30 {
+            a.step(1.0 / 60.0, &buf);
+        }
+
+        for i in 0..60 {
+            let shared_time = 0.5 + i as f64 / 60.0;
+            let a_output = a.step_to(shared_time, &buf).unwrap();
+            let b_output = b.step_to(shared_time, &buf).unwrap();
+
+            assert_eq!(a_output.value, b_output.value);
+            assert_eq!(a.playback_time, shared_time);
+            assert_eq!(a_output.time, shared_time);
+        }
+    }
+
+    #[test]
+    fn test_warmup_snapshots() {
+        let settings = leak(Settings {
+            warmup_snapshots: 5,
+            ..Settings::default()
+        });
+
+        let mut buf = crate::Buffer::new(settings);
+        let mut play = crate::Playback::new(&buf);
+
+        // A burst of snapshots on connecting, faster than the remote's rate
+        for i in 0..5 {
+            assert!(play.step(1.0 / 60.0, &buf).is_none());
+            let time = 10.0 + i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+        assert!(buf.remote_delta_time.value.is_some());
+
+        // Begins the offset behind the newest, not the first
+        let output = play.step(0.0, &buf).unwrap();
+        let offset = buf.dynamic_playback_offset() - play.frame_time.value.unwrap() / 2.0;
+        assert!((play.playback_time - (10.8 - offset)).abs() < 1e-3);
+        assert!((output.value - (10.8 - offset)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_one_euro_filter() {
+        #[derive(Copy, Clone, Debug)]
+        struct NoisySnapshot {
+            time: f64,
+            x: f64,
+        }
+
+        impl Snapshot for NoisySnapshot {
+            fn interpolate(t: f64, from: &Self, to: &Self) -> Self {
+                NoisySnapshot {
+                    time: lerp(from.time, to.time, t),
+                    x: lerp(from.x, to.x, t),
+                }
+            }
+
+            fn remote_time(&self) -> f64 {
+                self.time
+            }
+
+            fn filter_values(&mut self, visit: &mut dyn FnMut(&mut f64)) {
+                visit(&mut self.x);
+            }
+        }
+
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        for i in 0..10_u32 {
+            let time = i as f64 * 0.2;
+            let noise = if i.is_multiple_of(2) { 0.5 } else { -0.5 };
+            buf.insert_snapshot(NoisySnapshot { time, x: noise });
+        }
+
+        let mut raw = crate::Playback::new(&buf);
+        let mut filtered =
+            crate::Playback::new(&buf).with_one_euro_filter(crate::OneEuroFilter::new(0.5, 0.0));
+        raw.step(0.0, &buf);
+        filtered.step(0.0, &buf);
+        raw.playback_time = 0.2;
+        filtered.playback_time = 0.2;
+
+        let range = |play: &mut crate::Playback<NoisySnapshot>| {
+            let (mut min, mut max) = (f64::MAX, f64::MIN);
+            for _ in 0..60 {
+                let x = play.step(1.0 / 60.0, &buf).unwrap().x;
+                min = min.min(x);
+                max = max.max(x);
+            }
+            max - min
+        };
+
+        let raw_range = range(&mut raw);
+        let filtered_range = range(&mut filtered);
+        assert!(
+            filtered_range < raw_range * 0.5,
+            "{filtered_range} {raw_range}"
+        );
+    }
+
+    #[test]
+    fn test_segment_progress() {
+        use std::sync::{Arc, Mutex};
+
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        for i in 0..10 {
+            buf.insert_snapshot(TestSnapshot {
+                time: i as f64 * 0.2,
+                number: i,
+            });
+        }
+
+        let crossings = Arc::new(Mutex::new(Vec::new()));
+        let mut play = crate::Playback::new(&buf).with_progress_callback(vec![0.25, 0.5], {
+            let crossings = crossings.clone();
+            move |index, threshold| crossings.lock().unwrap().push((index, threshold))
+        });
+        play.step(0.0, &buf);
+        play.playback_time = 0.21;
+        play.timescale = 1.0;
+
+        let mut last_t = 0.0;
+        for _ in 0..10 {
+            play.step(1.0 / 60.0, &buf);
+            let (index, t) = play.last_segment_progress().unwrap();
+            assert_eq!(index, 8);
+            assert!(t > last_t, "{t} {last_t}");
+            last_t = t;
+        }
+
+        // Each threshold fired once, in order
+        assert_eq!(*crossings.lock().unwrap(), vec![(8, 0.25), (8, 0.5)]);
+
+        // Moving onto the next segment fires the thresholds passed in it
+        play.playback_time = 0.46;
+        play.step(0.0, &buf);
+        assert_eq!(play.last_segment_progress().unwrap().0, 7);
+        assert_eq!(
+            *crossings.lock().unwrap(),
+            vec![(8, 0.25), (8, 0.5), (7, 0.25)]
+        );
+
+        // Jumping several segments ahead in one step fires the thresholds
+        // of every segment passed through, in order
+        crossings.lock().unwrap().clear();
+        play.playback_time = 1.06;
+        play.step(0.0, &buf);
+        assert_eq!(play.last_segment_progress().unwrap().0, 4);
+        assert_eq!(
+            *crossings.lock().unwrap(),
+            vec![
+                (7, 0.5),
+                (6, 0.25),
+                (6, 0.5),
+                (5, 0.25),
+                (5, 0.5),
+                (4, 0.25)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_step_instant() {
+        use std::time::{Duration, Instant};
+
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        for i in 0..10 {
+            buf.insert_snapshot(ValueSnapshot {
+                time: i as f64 * 0.2,
+                value: i as f64,
+            });
+        }
+
+        let mut manual = crate::Playback::new(&buf);
+        let mut instant = crate::Playback::new(&buf);
+
+        let start = Instant::now();
+        manual.step(0.0, &buf);
+        instant.step_instant(start, &buf);
+        manual.playback_time = 0.2;
+        instant.playback_time = 0.2;
+        manual.timescale = 1.0;
+        instant.timescale = 1.0;
+
+        let frames = [16, 17, 16, 33, 8, 16, 0, 20];
+        let mut elapsed = Duration::ZERO;
+        let mut last = start;
+        for ms in frames {
+            elapsed += Duration::from_millis(ms);
+            let now = start + elapsed;
+            let delta_time = now.duration_since(last).as_secs_f64();
+            last = now;
+
+            let expected = manual.step(delta_time, &buf).unwrap();
+            let output = instant.step_instant(now, &buf).unwrap();
+            assert_eq!(output.value, expected.value);
+            assert_eq!(instant.playback_time, manual.playback_time);
+        }
+
+        // An instant before the last steps by zero
+        let playback_time = instant.playback_time;
+        instant.step_instant(start, &buf);
+        assert_eq!(instant.playback_time, playback_time);
+    }
+
+    #[test]
+    fn test_freeze_offset() {
+        let unfreeze_settings = leak(Settings {
+            offset_unfreeze_periods: Some(0.25),
+            ..Settings::default()
+        });
+
+        let mut time = 0.0;
+        let mut insert = |buf: &mut crate::Buffer<ValueSnapshot>, deltas: [f64; 2]| {
+            for i in 0..20 {
+                time += deltas[i % 2];
+                buf.insert_snapshot(ValueSnapshot { time, value: time });
+            }
+            buf.dynamic_playback_offset()
+        };
+
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let calm = insert(&mut buf, [0.19, 0.21]);
+        buf.freeze_offset();
+        assert!(buf.is_offset_frozen());
+
+        // Frozen despite the jitter
+        assert_eq!(insert(&mut buf, [0.05, 0.35]), calm);
+        assert_eq!(insert(&mut buf, [0.02, 0.38]), calm);
+
+        buf.unfreeze_offset();
+        assert!(buf.dynamic_playback_offset() > calm);
+
+        // Unfreezes itself once the jitter drastically changes
+        let mut buf = crate::Buffer::new(unfreeze_settings);
+        let calm = insert(&mut buf, [0.19, 0.21]);
+        buf.freeze_offset();
+        assert_eq!(insert(&mut buf, [0.15, 0.25]), calm);
+        assert!(buf.is_offset_frozen());
+        assert!(insert(&mut buf, [0.02, 0.38]) > calm);
+        assert!(!buf.is_offset_frozen());
+    }
+
+    #[test]
+    fn test_arc_length_reparam() {
+        #[derive(Copy, Clone, Debug)]
+        struct PathSnapshot {
+            time: f64,
+            x: f64,
+            y: f64,
+        }
+
+        impl Snapshot for PathSnapshot {
+            fn interpolate(t: f64, from: &Self, to: &Self) -> Self {
+                PathSnapshot {
+                    time: lerp(from.time, to.time, t),
+                    x: lerp(from.x, to.x, t),
+                    y: lerp(from.y, to.y, t),
+                }
+            }
+
+            fn interpolate_quadratic(t: f64, prev: &Self, from: &Self, to: &Self) -> Self {
+                PathSnapshot {
+                    time: lerp(from.time, to.time, t),
+                    x: crate::quad_bezier(prev.x, from.x, to.x, t),
+                    y: crate::quad_bezier(prev.y, from.y, to.y, t),
+                }
+            }
+
+            fn remote_time(&self) -> f64 {
+                self.time
+            }
+
+            fn arc_length(&self, other: &Self) -> f64 {
+                (other.x - self.x).hypot(other.y - self.y)
+            }
+        }
+
+        // A sharp turn, which the curve rounds unevenly
+        let path = [(0.0, 0.0), (1.0, 0.0), (1.0, 4.0), (1.0, 8.0), (1.0, 12.0)];
+
+        // The ratio of the fastest to slowest output speed through the turn
+        let speed_ratio = |reparam: bool| {
+            let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+            for (i, (x, y)) in path.into_iter().enumerate() {
+                buf.insert_snapshot(PathSnapshot {
+                    time: i as f64 * 0.2,
+                    x,
+                    y,
+                });
+            }
+
+            let mut play = crate::Playback::new(&buf);
+            if reparam {
+                play = play.with_arc_length_reparam();
+            }
+            play.step(0.0, &buf);
+            play.playback_time = 0.2;
+            play.timescale = 1.0;
+
+            let mut last = play.step(0.0, &buf).unwrap();
+            let (mut min, mut max) = (f64::MAX, f64::MIN);
+            for _ in 0..11 {
+                let output = play.step(1.0 / 60.0, &buf).unwrap();
+                let speed = last.arc_length(&output);
+                min = min.min(speed);
+                max = max.max(speed);
+                last = output;
+            }
+            max / min
+        };
+
+        let raw = speed_ratio(false);
+        let reparam = speed_ratio(true);
+        assert!(raw > 1.5, "{raw}");
+        assert!(reparam < 1.05, "{reparam}");
+    }
+
+    #[test]
+    fn test_last_skipped_count() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+
+        let mut play = crate::Playback::new(&buf);
+        play.step(0.0, &buf);
+        play.playback_time = 0.1;
+        play.timescale = 1.0;
+
+        // Crossing a single snapshot skips nothing
+        play.step(0.15, &buf);
+        assert_eq!(play.last_skipped_count(), 0);
+        play.step(0.01, &buf);
+        assert_eq!(play.last_skipped_count(), 0);
+
+        // Passing 0.4, 0.6, 0.8 and 1.0, and interpolating on from 1.0
+        play.step(0.8, &buf);
+        assert!(play.playback_time > 1.0 && play.playback_time < 1.2);
+        assert_eq!(play.last_skipped_count(), 3);
+    }
+
+    #[test]
+    fn test_output_quantize() {
+        #[derive(Copy, Clone, Debug)]
+        struct PixelSnapshot {
+            time: f64,
+            x: f64,
+        }
+
+        impl Snapshot for PixelSnapshot {
+            fn interpolate(t: f64, from: &Self, to: &Self) -> Self {
+                PixelSnapshot {
+                    time: lerp(from.time, to.time, t),
+                    x: lerp(from.x, to.x, t),
+                }
+            }
+
+            fn remote_time(&self) -> f64 {
+                self.time
+            }
+
+            fn quantize(&mut self, step: f64) {
+                self.x = (self.x / step).round() * step;
+            }
+        }
+
+        let settings = leak(Settings {
+            output_quantize: Some(4.0),
+            ..Settings::default()
+        });
+
+        let mut buf = crate::Buffer::new(settings);
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(PixelSnapshot {
+                time,
+                x: i as f64 * 10.0,
+            });
+        }
+
+        let mut play = crate::Playback::new(&buf);
+        play.step(0.0, &buf);
+        play.playback_time = 0.2;
+
+        let mut positions = Vec::new();
+        for _ in 0..24 {
+            let x = play.step(1.0 / 60.0, &buf).unwrap().x;
+            assert_eq!(x % 4.0, 0.0, "{x}");
+            positions.push(x);
+        }
+
+        // Still moving, in whole steps
+        assert!(positions.last() > positions.first());
+    }
+
+    #[test]
+    fn test_playback_group() {
+        let fast_settings = leak(Settings {
+            period: 0.05,
+            ..Settings::default()
+        });
+
+        // Animation state every 200ms, positions every 50ms
+        let mut slow_buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut fast_buf = crate::Buffer::new(fast_settings);
+        let mut group = crate::PlaybackGroup::new((
+            crate::Playback::new(&slow_buf),
+            crate::Playback::new(&fast_buf),
+        ));
+
+        for i in 0..100 {
+            let time = i as f64 * 0.05;
+            fast_buf.insert_snapshot(ValueSnapshot { time, value: time });
+            if i % 4 == 0 {
+                slow_buf.insert_snapshot(ValueSnapshot { time, value: time });
+            }
+
+            for _ in 0..3 {
+                let (slow, fast) = group.step(1.0 / 60.0, (&slow_buf, &fast_buf));
+                // Once the lead has a segment to play
+                if i >= 8 {
+                    let (slow, fast) = (slow.unwrap(), fast.unwrap());
+                    assert!((slow.value - fast.value).abs() < 1e-9, "{slow:?} {fast:?}");
+                }
+            }
+        }
+
+        // Both streams are being played, well into the session
+        assert!(group.remote_time() > 4.0, "{}", group.remote_time());
+    }
+
+    #[test]
+    fn test_playback_group_streams() {
+        let fast_settings = leak(Settings {
+            period: 0.05,
+            ..Settings::default()
+        });
+
+        // Animation state every 200ms, positions every 50ms, and health as
+        // a different type of snapshot every 100ms
+        let mut slow_buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut fast_buf = crate::Buffer::new(fast_settings);
+        let mut health_buf = crate::Buffer::new(fast_settings);
+        let mut group = crate::PlaybackGroup::new((
+            crate::Playback::new(&slow_buf),
+            crate::Playback::new(&fast_buf),
+            crate::Playback::new(&health_buf),
+        ));
+
+        for i in 0..100 {
+            let time = i as f64 * 0.05;
+            fast_buf.insert_snapshot(ValueSnapshot { time, value: time });
+            if i % 2 == 0 {
+                health_buf.insert_snapshot(TestSnapshot { time, number: i });
+            }
+            if i % 4 == 0 {
+                slow_buf.insert_snapshot(ValueSnapshot { time, value: time });
+            }
+
+            for _ in 0..3 {
+                let (slow, fast, health) =
+                    group.step(1.0 / 60.0, (&slow_buf, &fast_buf, &health_buf));
+                // Once the lead has a segment to play, all at the same time
+                if i >= 8 {
+                    let (slow, fast, health) = (slow.unwrap(), fast.unwrap(), health.unwrap());
+                    assert!((slow.value - fast.value).abs() < 1e-9, "{slow:?} {fast:?}");
+                    // Holding the end of the segment being played
+                    let ahead = health.time - slow.value;
+                    assert!((-1e-9..0.1 + 1e-9).contains(&ahead), "{health:?}");
+                }
+            }
+        }
+
+        assert!(group.remote_time() > 4.0, "{}", group.remote_time());
+    }
+
+    #[test]
+    fn test_validator() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        buf.set_validator(|s: &ValueSnapshot| (0.0..=100.0).contains(&s.value));
+
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            let value = if i % 3 == 0 { -50.0 } else { i as f64 };
+            let returned = buf.insert_snapshot(ValueSnapshot { time, value });
+            if i % 3 == 0 {
+                assert_eq!(returned.unwrap().value, -50.0);
+            }
+        }
+        buf.insert_snapshot(ValueSnapshot {
+            time: 2.0,
+            value: f64::NAN,
+        });
+
+        assert_eq!(buf.rejected_snapshots, 5);
+        assert_eq!(buf.iter().count(), 6);
+        assert!(buf.iter().all(|s| (0.0..=100.0).contains(&s.value)));
+    }
+
+    #[test]
+    fn test_catchup_loss_confidence() {
+        let confident_settings = leak(Settings {
+            catchup_loss_confidence: true,
+            ..Settings::default()
+        });
+
+        // The mean deviation of the timescale from 1.0
+        let run = |settings: &'static Settings, dropped: &dyn Fn(usize) -> bool| {
+            let mut buf = crate::Buffer::new(settings);
+            let mut play = crate::Playback::new(&buf);
+            let mut deviation = 0.0;
+            for i in 0..100 {
+                let time = i as f64 * 0.2;
+                if !dropped(i) {
+                    buf.insert_snapshot(ValueSnapshot { time, value: time });
+                }
+                for _ in 0..12 {
+                    play.step(1.0 / 60.0, &buf);
+                    deviation += (play.timescale - 1.0).abs();
+                }
+            }
+            deviation / 1200.0
+        };
+
+        // Bursts of five lost packets
+        let bursts = |i: usize| (2..7).contains(&(i % 10));
+
+        let clean = run(confident_settings, &|_| false);
+        let lossy = run(confident_settings, &bursts);
+        let lossy_unweighted = run(&SNAPSHOT_SETTINGS_DEFAULT, &bursts);
+
+        assert!(lossy < clean, "{lossy} {clean}");
+        assert!(lossy < lossy_unweighted, "{lossy} {lossy_unweighted}");
+    }
+
+    #[test]
+    fn test_extrapolation_override() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        for i in 0..6 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+
+        let mut projectile = crate::Playback::new(&buf);
+        let mut gauge = crate::Playback::new(&buf);
+        let mut hidden = crate::Playback::new(&buf);
+        let defaults = crate::ExtrapolationOverride::from_settings(&SNAPSHOT_SETTINGS_DEFAULT);
+        projectile.set_extrapolation_override(Some(crate::ExtrapolationOverride {
+            max_t: 2.0,
+            ..defaults
+        }));
+        gauge.set_extrapolation_override(Some(crate::ExtrapolationOverride {
+            allow: false,
+            ..defaults
+        }));
+        hidden.set_extrapolation_override(Some(crate::ExtrapolationOverride {
+            max_t: 2.0,
+            underrun: crate::Underrun::Hide,
+            ..defaults
+        }));
+
+        for play in [&mut projectile, &mut gauge, &mut hidden] {
+            play.step(0.0, &buf);
+            play.playback_time = 0.9;
+            play.timescale = 1.0;
+        }
+
+        // Starved well beyond the newest snapshot at 1.0
+        let mut hidden_outputs = Vec::new();
+        for _ in 0..30 {
+            projectile.step(1.0 / 60.0, &buf);
+            gauge.step(1.0 / 60.0, &buf);
+            hidden_outputs.push(hidden.step(1.0 / 60.0, &buf));
+        }
+
+        // Extrapolated as far as t of 2.0 through the newest segment
+        let projectile = projectile.step(0.0, &buf).unwrap();
+        assert!((projectile.value - 1.2).abs() < 1e-9, "{projectile:?}");
+
+        // Held at the newest snapshot
+        let gauge = gauge.step(0.0, &buf).unwrap();
+        assert!((gauge.value - 1.0).abs() < 1e-9, "{gauge:?}");
+
+        // Extrapolated, then hidden once extrapolated as far as allowed
+        assert!(hidden_outputs.iter().flatten().any(|s| s.value > 1.0));
+        assert!(hidden_outputs.last().unwrap().is_none());
+        assert!(hidden.step(0.0, &buf).is_none());
+
+        // Until a snapshot arrives
+        buf.insert_snapshot(ValueSnapshot {
+            time: 1.6,
+            value: 1.6,
+        });
+        assert!(hidden.step(0.0, &buf).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "max_t")]
+    fn test_extrapolation_override_max_t() {
+        let buf = crate::Buffer::<ValueSnapshot>::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+        play.set_extrapolation_override(Some(crate::ExtrapolationOverride {
+            max_t: 4.0,
+            ..crate::ExtrapolationOverride::from_settings(&SNAPSHOT_SETTINGS_DEFAULT)
+        }));
+    }
+
+    #[test]
+    fn test_optimal_offset() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        assert_eq!(buf.optimal_offset(0.01), 0.2_f32 as f64);
+
+        let mut time = 0.0;
+        for i in 0..100 {
+            time += [0.15, 0.25, 0.2, 0.18, 0.22][i % 5];
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+
+        let mean = buf.remote_delta_time.value.unwrap();
+        assert!((buf.optimal_offset(0.5) - mean).abs() < 1e-3);
+
+        let mut last = 0.0;
+        for prob in [0.5, 0.2, 0.05, 0.01, 0.001] {
+            let offset = buf.optimal_offset(prob);
+            assert!(offset > last, "{prob} {offset} {last}");
+            last = offset;
+        }
+
+        // Roughly two and a third standard deviations for a 1% chance
+        let std_dev = buf.remote_delta_time.std_dev;
+        assert!((buf.optimal_offset(0.01) - (mean + 2.326 * std_dev)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_valid_until() {
+        #[derive(Copy, Clone, Debug)]
+        struct ProjectileSnapshot {
+            time: f64,
+            x: f64,
+            despawn: f64,
+        }
+
+        impl Snapshot for ProjectileSnapshot {
+            fn interpolate(t: f64, from: &Self, to: &Self) -> Self {
+                ProjectileSnapshot {
+                    time: lerp(from.time, to.time, t),
+                    x: lerp(from.x, to.x, t),
+                    despawn: from.despawn,
+                }
+            }
+
+            fn remote_time(&self) -> f64 {
+                self.time
+            }
+
+            fn valid_until(&self) -> Option<f64> {
+                Some(self.despawn)
+            }
+        }
+
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        for i in 0..8 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ProjectileSnapshot {
+                time,
+                x: time,
+                despawn: 0.7,
+            });
+        }
+
+        let mut play = crate::Playback::new(&buf);
+        play.step(0.0, &buf);
+        play.playback_time = 0.2;
+        play.timescale = 1.0;
+
+        let mut emitted = Vec::new();
+        for _ in 0..60 {
+            if let Some(output) = play.step(1.0 / 60.0, &buf) {
+                emitted.push((play.playback_time, output.x));
+            }
+        }
+
+        // Emitted up until the despawn, and never after
+        assert!(!emitted.is_empty());
+        assert!(emitted.iter().all(|&(time, x)| time <= 0.7 && x <= 0.7));
+        assert!(emitted.last().unwrap().0 > 0.65, "{emitted:?}");
+        assert!(play.playback_time > 1.0);
+        assert!(play.sample(0.8, &buf).is_none());
+    }
+
+    #[test]
+    fn test_step_status() {
+        use crate::ScalingState;
+
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+
+        // Nothing buffered
+        let status = play.step(1.0 / 60.0, &buf);
+        assert!(status.is_none());
+        let status = play.step_status(1.0 / 60.0, &buf);
+        assert!(status.stalled && status.value.is_none());
+
+        // A single snapshot to hold
+        buf.insert_snapshot(ValueSnapshot {
+            time: 0.0,
+            value: 0.0,
+        });
+        let status = play.step_status(1.0 / 60.0, &buf);
+        assert!(status.stalled && status.value.is_some());
+
+        // Healthy
+        for i in 1..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+        play.playback_time = 1.6;
+        play.timescale = 1.0;
+        let status = play.step_status(1.0 / 60.0, &buf);
+        assert!(!status.stalled && !status.extrapolating && !status.clamped);
+        assert_eq!(status.scaling, ScalingState::Normal);
+        assert_eq!(status.value.unwrap().value, play.playback_time);
+
+        // Starved
+        play.playback_time = 1.9;
+        let status = play.step_status(1.0 / 60.0, &buf);
+        assert!(status.extrapolating && !status.stalled);
+
+        // Far behind as a snapshot arrives
+        play.playback_time = 1.0;
+        buf.insert_snapshot(ValueSnapshot {
+            time: 2.0,
+            value: 2.0,
+        });
+        let status = play.step_status(1.0 / 60.0, &buf);
+        assert!(status.clamped);
+
+        // Persistently behind, then ahead, of the target as snapshots arrive
+        let mut time = 2.0;
+        let mut arrive = |play: &mut crate::Playback<ValueSnapshot>, lag: f64| {
+            time += 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+            play.playback_time = play.target_playback_time(&buf) - lag;
+            play.step_status(0.0, &buf)
+        };
+        for _ in 0..5 {
+            arrive(&mut play, 0.15);
+        }
+        let status = arrive(&mut play, 0.15);
+        assert!(!status.clamped);
+        assert_eq!(status.scaling, ScalingState::Fast);
+
+        for _ in 0..5 {
+            arrive(&mut play, -0.15);
+        }
+        assert_eq!(arrive(&mut play, -0.15).scaling, ScalingState::Slow);
+    }
+
+    #[test]
+    fn test_iter_chronological() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        for time in [0.4, 0.0, 0.2, 0.8, 0.6, 0.6, 1.0] {
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+
+        let times: Vec<f64> = buf.iter_chronological().map(|s| s.time).collect();
+        assert_eq!(times, vec![0.0, 0.2, 0.4, 0.6, 0.8, 1.0]);
+        assert!(times.windows(2).all(|w| w[0] < w[1]));
+
+        let mut newest_first: Vec<f64> = buf.iter().map(|s| s.time).collect();
+        newest_first.reverse();
+        assert_eq!(times, newest_first);
+    }
+
+    #[test]
+    fn test_ease_in_from() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+        let mut reference = crate::Playback::new(&buf);
+
+        let spawn = ValueSnapshot {
+            time: 0.0,
+            value: -10.0,
+        };
+        play.ease_in_from(spawn, 2.0);
+
+        // Held at the origin until there's data
+        assert_eq!(play.step(0.1, &buf).unwrap().value, -10.0);
+
+        for i in 0..10 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+        for play in [&mut play, &mut reference] {
+            play.step(0.0, &buf);
+            play.playback_time = 0.4;
+            play.timescale = 1.0;
+        }
+
+        // Starts at the origin
+        let first = play.step(0.0, &buf).unwrap();
+        reference.step(0.0, &buf);
+        assert_eq!(first.value, -10.0);
+
+        // Converging over the two periods
+        let mut last_gap = f64::MAX;
+        for _ in 0..24 {
+            let eased = play.step(1.0 / 60.0, &buf).unwrap();
+            let target = reference.step(1.0 / 60.0, &buf).unwrap();
+            let gap = (target.value - eased.value).abs();
+            assert!(gap < last_gap, "{gap} {last_gap}");
+            last_gap = gap;
+        }
+
+        // Then exactly the buffered interpolation
+        let eased = play.step(1.0 / 60.0, &buf).unwrap();
+        let target = reference.step(1.0 / 60.0, &buf).unwrap();
+        assert_eq!(eased.value, target.value);
+    }
+
+    #[test]
+    fn test_boundary_bracket() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+        for i in 0..8 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+        play.step(0.0, &buf);
+
+        // Exactly on a snapshot, the target time, with and without arrivals,
+        // always selects the segment ending at it
+        for i in 8..16 {
+            if i % 2 == 0 {
+                let time = i as f64 * 0.2;
+                buf.insert_snapshot(ValueSnapshot { time, value: time });
+            }
+            let boundary = buf.iter().nth(1).unwrap().time;
+            let before = buf.iter().nth(2).unwrap().time;
+            for _ in 0..3 {
+                play.playback_time = boundary;
+                play.timescale = 1.0;
+                let (output, from, to) = play.step_with_neighbors(0.0, &buf).unwrap();
+                assert!(play.last_clamp().is_none());
+                assert_eq!((from.time, to.time), (before, boundary));
+                assert_eq!(output.value, boundary);
+            }
+        }
+
+        // Clamped as a snapshot arrives, interpolating the segment the
+        // playback time was clamped onto
+        play.playback_time = 0.5;
+        buf.insert_snapshot(ValueSnapshot {
+            time: 3.2,
+            value: 3.2,
+        });
+        let (output, from, to) = play.step_with_neighbors(0.0, &buf).unwrap();
+        assert!(play.last_clamp().is_some());
+        assert!(from.time < play.playback_time && play.playback_time <= to.time);
+        assert!((output.value - play.playback_time).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_disable_timescale() {
+        let settings = leak(Settings {
+            disable_timescale: true,
+            ..Settings::default()
+        });
+
+        let run = |settings: &'static Settings| {
+            let mut buf = crate::Buffer::new(settings);
+            let mut play = crate::Playback::new(&buf);
+            let mut timescales = Vec::new();
+            // The remote's clock runs 10% fast of the local clock
+            for i in 0..60 {
+                let time = i as f64 * 0.22;
+                buf.insert_snapshot(ValueSnapshot { time, value: time });
+                for _ in 0..12 {
+                    play.step(1.0 / 60.0, &buf);
+                    timescales.push(play.timescale);
+                }
+            }
+            (timescales, play.catchup_time.value)
+        };
+
+        let (drifting, _) = run(&SNAPSHOT_SETTINGS_DEFAULT);
+        assert!(drifting.iter().any(|&timescale| timescale != 1.0));
+
+        let (timescales, catchup_time) = run(settings);
+        assert!(timescales.iter().all(|&timescale| timescale == 1.0));
+        assert_eq!(catchup_time, None);
+    }
+
+    #[test]
+    fn test_frame_time_offset() {
+        // The mean distance the playback settles behind the remote
+        let settled_offset = |fps: usize| {
+            let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+            let mut play = crate::Playback::new(&buf);
+            let (mut total, mut samples) = (0.0, 0);
+            for i in 0..100 {
+                let time = i as f64 * 0.2;
+                buf.insert_snapshot(ValueSnapshot { time, value: time });
+                for _ in 0..fps / 5 {
+                    play.step(1.0 / fps as f64, &buf);
+                    if i >= 50 {
+                        total += buf.estimated_remote_now() - play.playback_time;
+                        samples += 1;
+                    }
+                }
+            }
+            total / samples as f64
+        };
+
+        let slow = settled_offset(30);
+        let fast = settled_offset(145);
+        assert!((slow - fast).abs() < 3e-3, "{slow} {fast}");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_save_restore() {
+        let snapshot = |i: usize| {
+            let time = i as f64 * 0.2;
+            ValueSnapshot {
+                time,
+                value: (time * 3.0).sin(),
+            }
+        };
+        // Jittered frame times, so that the playback's measurements matter
+        let frame = |i: usize, j: usize| 1.0 / 60.0 + ((i * 12 + j) % 7) as f64 * 0.001;
+
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+        let mut uninterrupted = Vec::new();
+        for i in 0..60 {
+            buf.insert_snapshot(snapshot(i));
+            for j in 0..12 {
+                let value = play.step(frame(i, j), &buf).map(|s| s.value);
+                if i >= 30 {
+                    uninterrupted.push(value);
+                }
+            }
+        }
+
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+        for i in 0..30 {
+            buf.insert_snapshot(snapshot(i));
+            for j in 0..12 {
+                play.step(frame(i, j), &buf);
+            }
+        }
+        let saved_buf = serde_json::to_string(&buf.save()).unwrap();
+        let saved_play = serde_json::to_string(&play.save()).unwrap();
+
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+        buf.restore(serde_json::from_str(&saved_buf).unwrap());
+        play.restore(serde_json::from_str(&saved_play).unwrap());
+        let mut resumed = Vec::new();
+        for i in 30..60 {
+            buf.insert_snapshot(snapshot(i));
+            for j in 0..12 {
+                resumed.push(play.step(frame(i, j), &buf).map(|s| s.value));
+            }
+        }
+
+        assert_eq!(resumed.len(), uninterrupted.len());
+        for (resumed, uninterrupted) in resumed.iter().zip(&uninterrupted) {
+            let (resumed, uninterrupted) = (resumed.unwrap(), uninterrupted.unwrap());
+            assert!((resumed - uninterrupted).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_max_interp_hz() {
+        use std::sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        };
+
+        let settings = leak(Settings {
+            max_interp_hz: Some(30.0),
+            ..Settings::default()
+        });
+        let interpolations = Arc::new(AtomicUsize::new(0));
+
+        let mut buf = crate::Buffer::new(settings);
+        let counter = interpolations.clone();
+        let mut play = crate::Playback::new(&buf).with_interpolate_fn(move |t, from, to| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            ValueSnapshot::interpolate(t, from, to)
+        });
+        let mut outputs = Vec::new();
+        for i in 0..50 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+            if i == 25 {
+                interpolations.store(0, Ordering::Relaxed);
+            }
+            for _ in 0..24 {
+                outputs.push(play.step(1.0 / 120.0, &buf).map(|s| s.value));
+            }
+        }
+
+        // 5 seconds at 30Hz, rather than 120Hz
+        let interpolations = interpolations.load(Ordering::Relaxed);
+        assert!((148..=152).contains(&interpolations), "{interpolations}");
+        // Held between interpolations, while the playback time kept going
+        assert_eq!(outputs[1000], outputs[1001]);
+        assert!(outputs[1000] < outputs[1004]);
+
+        // Jumping the playback time is rate limited too, 1 second at 30Hz
+        let counter = Arc::new(AtomicUsize::new(0));
+        let step_to_counter = counter.clone();
+        let mut play = crate::Playback::new(&buf).with_interpolate_fn(move |t, from, to| {
+            step_to_counter.fetch_add(1, Ordering::Relaxed);
+            ValueSnapshot::interpolate(t, from, to)
+        });
+        for i in 0..120 {
+            play.step_to(8.0 + i as f64 / 120.0, &buf);
+        }
+        let interpolations = counter.load(Ordering::Relaxed);
+        assert!((29..=31).contains(&interpolations), "{interpolations}");
+
+        // As is interpolating within a frame, the output changing 30 times
+        // a second
+        let mut buf = crate::Buffer::new(settings);
+        let mut play = crate::Playback::new(&buf);
+        let mut outputs = Vec::new();
+        for i in 0..50 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+            for _ in 0..24 {
+                outputs.push(play.step_in_frame(1.0 / 120.0, &buf, &0.0).map(|s| s.value));
+            }
+        }
+        let changes = outputs[600..]
+            .windows(2)
+            .filter(|pair| pair[0] != pair[1])
+            .count();
+        assert!((148..=152).contains(&changes), "{changes}");
+    }
+
+    #[test]
+    fn test_recent_deltas() {
+        let settings = leak(Settings {
+            recent_deltas_len: 4,
+            ..Settings::default()
+        });
+        let mut buf = crate::Buffer::new(settings);
+        assert!(buf.recent_deltas().is_empty());
+
+        // Every other packet sent 50ms later, e.g. over a second path
+        let mut time = 0.0;
+        for i in 0..20 {
+            time += if i % 2 == 0 { 0.15 } else { 0.25 };
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+
+        let deltas = buf.recent_deltas();
+        assert_eq!(deltas.len(), 4);
+        for (i, &delta) in deltas.iter().enumerate() {
+            let expected = if i % 2 == 0 { 0.25 } else { 0.15 };
+            assert!((delta - expected).abs() < 1e-9, "{deltas:?}");
+        }
+
+        // Neither a duplicate nor a late arrival is a new time between
+        // packets
+        let deltas = buf.recent_deltas().to_vec();
+        let remote_delta_time = buf.remote_delta_time.value;
+        buf.insert_snapshot(ValueSnapshot { time, value: time });
+        buf.insert_snapshot(ValueSnapshot {
+            time: time - 0.3,
+            value: time - 0.3,
+        });
+        assert_eq!(buf.recent_deltas(), deltas);
+        assert_eq!(buf.remote_delta_time.value, remote_delta_time);
+    }
+
+    #[test]
+    fn test_insert_received_at() {
+        use std::time::{Duration, Instant};
+
+        // A captured trace, local receive times (seconds) of jittery
+        // snapshots and the local frame times
+        let arrivals: Vec<(f64, f64)> = (0..40_u32)
+            .map(|i| (i as f64 * 0.2, i as f64 * 0.2 + 0.05 * (i % 3) as f64))
+            .collect();
+        let frames: Vec<f64> = (0..500_u32).map(|i| i as f64 / 60.0).collect();
+
+        let replay = |start: Instant| {
+            let at = |seconds: f64| start + Duration::from_secs_f64(seconds);
+            let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+            let mut play = crate::Playback::new(&buf);
+            let mut arrivals = arrivals.iter().peekable();
+            let mut outputs = Vec::new();
+            for &frame in &frames {
+                while let Some(&&(time, received)) = arrivals.peek()
+                    && received <= frame
+                {
+                    buf.insert_snapshot_received_at(
+                        ValueSnapshot { time, value: time },
+                        at(received),
+                    );
+                    arrivals.next();
+                }
+                outputs.push(play.step_instant(at(frame), &buf).map(|s| s.value));
+            }
+            (outputs, play.dwell_time.value)
+        };
+
+        // Replayed later, the wall clock has moved on but the run hasn't
+        let original = replay(Instant::now());
+        std::thread::sleep(Duration::from_millis(20));
+        let replayed = replay(Instant::now());
+        assert!(original.0.iter().any(Option::is_some));
+        assert_eq!(original, replayed);
+        assert!(original.1.is_some_and(|dwell_time| dwell_time > 0.0));
+    }
+
+    #[test]
+    fn test_not_extrapolatable() {
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        struct AmmoSnapshot {
+            time: f64,
+            ammo: u32,
+        }
+
+        impl Snapshot for AmmoSnapshot {
+            const EXTRAPOLATABLE: bool = false;
+
+            fn interpolate(t: f64, from: &Self, to: &Self) -> Self {
+                AmmoSnapshot {
+                    time: lerp(from.time, to.time, t),
+                    ammo: lerp(from.ammo as f64, to.ammo as f64, t).round().max(0.0) as u32,
+                }
+            }
+
+            fn remote_time(&self) -> f64 {
+                self.time
+            }
+
+            fn clamp(&mut self) {
+                self.ammo = self.ammo.min(100);
+            }
+        }
+
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+        for i in 0..20_u32 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(AmmoSnapshot {
+                time,
+                ammo: 120 - i,
+            });
+            for _ in 0..12 {
+                play.step(1.0 / 60.0, &buf);
+            }
+        }
+
+        // A gap in the stream, which would otherwise extrapolate. Held on
+        // the newest snapshot, still clamped
+        let newest = *buf.latest().unwrap();
+        let held = AmmoSnapshot {
+            ammo: 100,
+            ..newest
+        };
+        let mut extrapolated = false;
+        for _ in 0..60 {
+            let output = play.step(1.0 / 60.0, &buf).unwrap();
+            if play.playback_time + play.time_epoch() > newest.time {
+                extrapolated = true;
+                assert_eq!(output, held);
+            }
+        }
+        assert!(extrapolated);
+
+        let (output, t, from_pos, to_pos) = buf.sample_at_detailed(newest.time + 0.1).unwrap();
+        assert_eq!(output, held);
+        assert_eq!((t, from_pos, to_pos), (1.0, 1, 0));
+    }
+
+    #[test]
+    fn test_raw_passthrough() {
+        let settings = leak(Settings {
+            raw_passthrough: true,
+            ..Settings::default()
+        });
+        let mut buf = crate::Buffer::new(settings);
+        let mut play = crate::Playback::new(&buf);
+        assert!(play.step(1.0 / 60.0, &buf).is_none());
+
+        for i in 0..20 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot {
+                time,
+                value: time * 7.0,
+            });
+            for _ in 0..12 {
+                let output = play.step(1.0 / 60.0, &buf).unwrap();
+                assert_eq!(output.time, time);
+                assert_eq!(output.value, time * 7.0);
+            }
+        }
+        assert_eq!(play.playback_time, 0.0);
+
+        // Every kind of step passes through
+        let newest = *buf.latest().unwrap();
+        for output in [
+            play.step_to(1.3, &buf),
+            play.step_reverse(1.0 / 60.0, &buf),
+            play.step_in_frame(1.0 / 60.0, &buf, &100.0),
+        ] {
+            let output = output.unwrap();
+            assert_eq!((output.time, output.value), (newest.time, newest.value));
+        }
+        assert_eq!(play.playback_time, 0.0);
+
+        // Between the newest two
+        let (output, from, to) = play.step_with_neighbors(1.0 / 60.0, &buf).unwrap();
+        let second = buf.iter().nth(1).unwrap().time;
+        assert_eq!(
+            (output.time, from.time, to.time),
+            (newest.time, second, newest.time)
+        );
+        let status = play.step_status(1.0 / 60.0, &buf);
+        assert_eq!(status.value.unwrap().time, newest.time);
+        assert!(!status.stalled && !status.extrapolating && !status.clamped);
+
+        // Or the newest alone
+        let mut buf = crate::Buffer::new(settings);
+        let mut play = crate::Playback::new(&buf);
+        assert!(play.step_with_neighbors(1.0 / 60.0, &buf).is_none());
+        assert!(play.step_status(1.0 / 60.0, &buf).stalled);
+        buf.insert_snapshot(ValueSnapshot {
+            time: 0.0,
+            value: 1.0,
+        });
+        let (output, from, to) = play.step_with_neighbors(1.0 / 60.0, &buf).unwrap();
+        assert_eq!((output.value, from.value, to.value), (1.0, 1.0, 1.0));
+        assert!(!play.step_status(1.0 / 60.0, &buf).stalled);
+    }
+
+    #[test]
+    fn test_offset_quantum() {
+        let settings = leak(Settings {
+            offset_quantum_ms: Some(10.0),
+            ..Settings::default()
+        });
+        let mut buf = crate::Buffer::new(settings);
+        let mut unquantized = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+
+        // Packets alternately `jitter` early and late
+        let mut time = 0.0;
+        let mut feed = |jitter: f64| {
+            for i in 0..40 {
+                time += if i % 2 == 0 {
+                    0.2 - jitter
+                } else {
+                    0.2 + jitter
+                };
+                buf.insert_snapshot(ValueSnapshot { time, value: time });
+                unquantized.insert_snapshot(ValueSnapshot { time, value: time });
+            }
+            (
+                buf.dynamic_playback_offset(),
+                unquantized.dynamic_playback_offset(),
+            )
+        };
+
+        let (calm, calm_unquantized) = feed(0.002);
+        let (fluctuated, fluctuated_unquantized) = feed(0.003);
+        assert!(fluctuated_unquantized > calm_unquantized);
+        assert_eq!(fluctuated, calm);
+        assert!((calm - 0.2).abs() < 1e-9, "{calm}");
+
+        let (jittery, _) = feed(0.03);
+        assert!(jittery > calm + 0.01, "{jittery}");
+        assert!((jittery * 100.0 - (jittery * 100.0).round()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_debug_report() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+
+        // Before anything has arrived
+        play.step(1.0 / 60.0, &buf);
+        let report = play.debug_report(&buf);
+        assert!(report.contains("buffered          0/"), "{report}");
+
+        for i in 0..20 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+            for _ in 0..12 {
+                play.step(1.0 / 60.0, &buf);
+            }
+        }
+        let report = play.debug_report(&buf);
+        for field in [
+            "playback time",
+            "timescale",
+            "catchup time",
+            "drift",
+            "playback offset",
+            "remote delta time",
+            "buffered",
+            "dbg_extrapolating",
+            "dbg_clamping",
+            "dbg_scaling",
+        ] {
+            assert!(report.contains(field), "{field} missing from {report}");
+        }
+        assert!(report.contains("remote delta time 200.0ms"), "{report}");
+    }
+
+    #[test]
+    fn test_insert_with_offset() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+
+        // Two sources sampling the same ramp, the second's clock 100ms behind
+        let lag = 0.1;
+        let mut outputs = Vec::new();
+        for i in 0..30 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+            let lagging = time + 0.15;
+            buf.insert_snapshot_with_offset(
+                ValueSnapshot {
+                    time: lagging - lag,
+                    value: lagging,
+                },
+                lag,
+            );
+            for _ in 0..12 {
+                outputs.push((
+                    play.step(1.0 / 60.0, &buf),
+                    play.time_epoch() + play.playback_time,
+                ));
+            }
+        }
+
+        // Interleaved by the offset remote times, the snapshots unaltered
+        let values: Vec<f64> = buf.iter_chronological().map(|s| s.value).collect();
+        assert!(
+            values.windows(2).all(|pair| pair[0] < pair[1]),
+            "{values:?}"
+        );
+        assert!(buf.iter().any(|s| (s.value - s.time - lag).abs() < 1e-9));
+        for snapshot in buf.iter() {
+            assert_eq!(buf.remote_time_of(snapshot), snapshot.value);
+        }
+
+        // Sampled exactly along the ramp
+        let (sample, ..) = buf.sample_at_detailed(5.7).unwrap();
+        assert!((sample.value - 5.7).abs() < 1e-9);
+        for (output, playback_time) in &outputs[180..] {
+            let output = output.unwrap();
+            assert!(
+                (output.value - playback_time).abs() < 1e-6,
+                "{output:?} at {playback_time}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_max_buf_len() {
+        assert_eq!(SNAPSHOT_SETTINGS_DEFAULT.buf_len(), 10);
+        assert!(!SNAPSHOT_SETTINGS_DEFAULT.is_buf_len_capped());
+
+        // 1000Hz for 30 seconds
+        let settings = leak(Settings {
+            period: 0.001,
+            buf_duration: 30.0,
+            ..Settings::default()
+        });
+        assert!(settings.is_buf_len_capped());
+        assert_eq!(settings.buf_len(), settings.max_buf_len);
+
+        let mut buf = crate::Buffer::new(settings);
+        assert_eq!(buf.max_len(), settings.max_buf_len);
+        for i in 0..2000 {
+            let time = i as f64 * 0.001;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+        }
+        assert_eq!(buf.iter().count(), settings.max_buf_len);
+
+        // Would otherwise overflow allocating the buffer
+        let zero_period = leak(Settings {
+            period: 0.0,
+            ..Settings::default()
+        });
+        assert_eq!(
+            crate::Buffer::<ValueSnapshot>::new(zero_period).max_len(),
+            1024
+        );
+    }
+
+    #[test]
+    fn test_effective_remote_dt() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+
+        let mut seeded_at = None;
+        let (mut traversed, mut advanced) = (0.0, 0.0);
+        for i in 0..100_u32 {
+            // Jittery arrivals, so that the playback scales and clamps
+            let time = i as f64 * 0.2 + 0.08 * (i % 3) as f64;
+            buf.insert_snapshot(ValueSnapshot { time, value: time });
+            for _ in 0..12 {
+                play.step(1.0 / 60.0, &buf);
+                traversed += play.effective_remote_dt();
+                advanced += play.last_advance();
+                seeded_at.get_or_insert(play.playback_time - play.effective_remote_dt());
+            }
+        }
+
+        let seeded_at = seeded_at.unwrap();
+        assert!((traversed - (play.playback_time - seeded_at)).abs() < 1e-9);
+        // Unlike `last_advance`, which includes seeding
+        assert!((advanced - play.playback_time).abs() < 1e-9);
+        assert!(seeded_at != 0.0);
+    }
+
+    #[test]
+    fn test_soft_clamp() {
+        let settings = leak(Settings {
+            soft_clamp_periods: Some(2.0),
+            ..Settings::default()
+        });
+
+        // The largest advance in a single frame after the remote skips 2
+        // seconds ahead, and whether the playback ended within the clamp
+        let run = |settings: &'static Settings| {
+            let mut buf = crate::Buffer::new(settings);
+            let mut play = crate::Playback::new(&buf);
+            let mut largest_advance: f64 = 0.0;
+            for i in 0..60 {
+                let time = i as f64 * 0.2 + if i >= 30 { 2.0 } else { 0.0 };
+                buf.insert_snapshot(ValueSnapshot { time, value: time });
+                for _ in 0..12 {
+                    play.step(1.0 / 60.0, &buf);
+                    if i >= 30 {
+                        largest_advance = largest_advance.max(play.last_advance());
+                    }
+                }
+            }
+            let within_clamp =
+                play.instantaneous_error(&buf).abs() <= settings.playback_clamp() as f64;
+            (largest_advance, within_clamp)
+        };
+
+        let (hard_advance, hard_converged) = run(&SNAPSHOT_SETTINGS_DEFAULT);
+        assert!(hard_advance > 1.0, "{hard_advance}");
+        assert!(hard_converged);
+
+        let (soft_advance, soft_converged) = run(settings);
+        assert!(soft_advance < 0.2, "{soft_advance}");
+        assert!(soft_converged);
+    }
+
+    #[test]
+    fn test_max_presentation_latency() {
+        let latency = |playback_clamp_periods: f32| {
+            let settings = leak(Settings {
+                playback_clamp_periods,
+                ..Settings::default()
+            });
+            let mut buf = crate::Buffer::new(settings);
+            let play = crate::Playback::new(&buf);
+            for i in 0..20_u32 {
+                let time = i as f64 * 0.2 + 0.03 * (i % 2) as f64;
+                buf.insert_snapshot(ValueSnapshot { time, value: time });
+            }
+            (
+                play.max_presentation_latency(&buf),
+                buf.dynamic_playback_offset(),
+            )
+        };
+
+        let (narrow, offset) = latency(0.5);
+        assert!(narrow >= offset);
+        assert!(offset > SNAPSHOT_SETTINGS_DEFAULT.playback_offset() as f64);
+
+        let (wide, _) = latency(2.0);
+        assert!((wide - narrow - 0.3).abs() < 1e-6, "{wide} {narrow}");
+    }
+
+    #[test]
+    fn test_blend_playbacks() {
+        let mut buf_a = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut buf_b = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play_a = crate::Playback::new(&buf_a);
+        let mut play_b = crate::Playback::new(&buf_b);
+        assert!(crate::blend_playbacks(&play_a, &play_b, &buf_a, &buf_b, 0.5).is_none());
+
+        for i in 0..20 {
+            let time = i as f64 * 0.2;
+            buf_a.insert_snapshot(ValueSnapshot { time, value: time });
+            buf_b.insert_snapshot(ValueSnapshot {
+                time,
+                value: 100.0 - time * 3.0,
+            });
+            for _ in 0..12 {
+                let a = play_a.step(1.0 / 60.0, &buf_a).unwrap();
+                let b = play_b.step(1.0 / 60.0, &buf_b).unwrap();
+
+                let blend = |alpha| {
+                    crate::blend_playbacks(&play_a, &play_b, &buf_a, &buf_b, alpha).unwrap()
+                };
+                assert_eq!(blend(0.0).value, a.value);
+                assert_eq!(blend(1.0).value, b.value);
+                let half = blend(0.5).value;
+                assert!((half - (a.value + b.value) / 2.0).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_min_scaling_hold() {
+        let settings = leak(Settings {
+            min_scaling_hold_periods: Some(2.0),
+            ..Settings::default()
+        });
+
+        // The timescales from just before the playback falls behind by more
+        // than the fast threshold on each arrival for which `behind` holds,
+        // once settled
+        let run = |settings: &'static Settings, behind: fn(usize) -> bool| {
+            let mut buf = crate::Buffer::new(settings);
+            let mut play = crate::Playback::new(&buf);
+            let mut timescales = Vec::new();
+            for i in 0..100 {
+                let time = i as f64 * 0.2;
+                buf.insert_snapshot(ValueSnapshot { time, value: time });
+                if i == 60 {
+                    // Respond to each arrival's catchup time immediately
+                    play.catchup_time = crate::ExponentialMovingAverage::new(1.0);
+                }
+                let behind = i >= 60 && behind(i);
+                if behind {
+                    play.playback_time -= 0.15;
+                }
+                play.step(1.0 / 60.0, &buf);
+                if behind {
+                    play.playback_time += 0.15;
+                }
+                for _ in 0..11 {
+                    play.step(1.0 / 60.0, &buf);
+                }
+                if i >= 59 {
+                    timescales.push(play.timescale);
+                }
+            }
+            timescales
+        };
+        let brief = |i| i == 60;
+        let alternating = |i| i % 2 == 0;
+
+        // The arrivals at which the timescale changed
+        let changes = |timescales: &[f64]| -> Vec<usize> {
+            (1..timescales.len())
+                .filter(|&i| timescales[i] != timescales[i - 1])
+                .collect()
+        };
+
+        // Without the hold, the timescale toggles from one arrival to the next
+        let unheld = run(&SNAPSHOT_SETTINGS_DEFAULT, brief);
+        assert_eq!(changes(&unheld), [1, 2]);
+        let unheld = run(&SNAPSHOT_SETTINGS_DEFAULT, alternating);
+        assert!(changes(&unheld).windows(2).all(|w| w[1] - w[0] == 1));
+
+        // With it, the timescale still changes at once, but then holds for
+        // two periods (arrivals) before changing again
+        for timescales in [run(settings, brief), run(settings, alternating)] {
+            let changes = changes(&timescales);
+            assert_eq!(changes[0], 1, "{timescales:?}");
+            assert!(
+                changes.windows(2).all(|w| w[1] - w[0] >= 2),
+                "{timescales:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sample_offset() {
+        let mut buf = crate::Buffer::new(&SNAPSHOT_SETTINGS_DEFAULT);
+        let mut play = crate::Playback::new(&buf);
+        for i in 0..20 {
+            let time = i as f64 * 0.2;
+            buf.insert_snapshot(ValueSnapshot {
+                time,
+                value: time * 2.0,
+            });
+            for _ in 0..12 {
+                let output = play.step(1.0 / 60.0, &buf).unwrap();
+                assert_eq!(play.sample_offset(0.0, &buf).unwrap().value, output.value);
+            }
+        }
+
+        // Shutter samples across the last frame, oldest first
+        let playback_time = play.playback_time;
+        let samples: Vec<f64> = (0..=8)
+            .rev()
+            .map(|i| {
+                let before = i as f64 / 8.0 / 60.0;
+                play.sample_offset(before, &buf).unwrap().value
+            })
+            .collect();
+        assert!(
+            samples.windows(2).all(|pair| pair[0] < pair[1]),
+            "{samples:?}"
+        );
+        // Spanning a frame of the ramp
+        let span = samples[8] - samples[0];
+        assert!((span - 2.0 / 60.0).abs() < 1e-9, "{span}");
+        assert_eq!(play.playback_time, playback_time);
+    }
 }
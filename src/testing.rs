@@ -0,0 +1,490 @@
+//! Test support for measuring interpolation quality against a known
+//! ground truth trajectory. Enable with the `testing` feature.
+
+use std::time::{Duration, Instant};
+
+use crate::{Buffer, Playback, Settings, Snapshot};
+
+/// Measure how far apart two snapshots are, for computing the error
+/// between interpolated output and ground truth.
+pub trait Distance {
+    fn distance(&self, other: &Self) -> f64;
+}
+
+/// Simulated network conditions between the remote and the local client.
+#[derive(Clone, Debug)]
+pub struct NetworkProfile {
+    /// Mean one-way latency (seconds)
+    pub latency: f64,
+
+    /// Standard deviation of the latency (seconds)
+    pub jitter: f64,
+
+    /// Probability of any one snapshot being dropped, between 0.0 and 1.0
+    pub drop_rate: f64,
+
+    /// The time between two local steps (seconds)
+    pub frame_time: f64,
+
+    /// How long to run the simulation for (seconds)
+    pub duration: f64,
+
+    /// Initial period during which errors aren't measured, giving the
+    /// playback time to settle (seconds)
+    pub warmup: f64,
+
+    pub seed: u64,
+}
+
+impl Default for NetworkProfile {
+    fn default() -> Self {
+        NetworkProfile {
+            latency: 0.0,
+            jitter: 0.0,
+            drop_rate: 0.0,
+
+            frame_time: 1.0 / 60.0,
+            duration: 30.0,
+            warmup: 2.0,
+
+            seed: 0x5EED,
+        }
+    }
+}
+
+impl NetworkProfile {
+    /// No latency, no jitter and no loss
+    pub fn perfect() -> Self {
+        NetworkProfile::default()
+    }
+
+    pub fn good() -> Self {
+        NetworkProfile {
+            latency: 0.010,
+            jitter: 0.002,
+            drop_rate: 0.001,
+            ..NetworkProfile::default()
+        }
+    }
+
+    pub fn fair() -> Self {
+        NetworkProfile {
+            latency: 0.080,
+            jitter: 0.020,
+            drop_rate: 0.005,
+            ..NetworkProfile::default()
+        }
+    }
+
+    /// A cellular connection, high and variable latency with some loss
+    pub fn mobile() -> Self {
+        NetworkProfile {
+            latency: 0.120,
+            jitter: 0.050,
+            drop_rate: 0.02,
+            ..NetworkProfile::default()
+        }
+    }
+
+    pub fn poor() -> Self {
+        NetworkProfile {
+            latency: 0.300,
+            jitter: 0.150,
+            drop_rate: 0.2,
+            ..NetworkProfile::default()
+        }
+    }
+}
+
+/// The result of [`evaluate`]
+#[derive(Clone, Debug, Default)]
+pub struct EvalReport {
+    /// Root mean square of the error between the output and the ground truth
+    pub rms_error: f64,
+
+    /// The largest error between the output and the ground truth
+    pub max_error: f64,
+
+    /// How many of the measured steps relied on extrapolation, between
+    /// 1.0 - all, and 0.0 - none.
+    pub extrapolation_fraction: f64,
+
+    /// The number of measured steps
+    pub steps: usize,
+}
+
+/// A small deterministic xorshift generator so that simulations are
+/// reproducible without depending on `rand`.
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Rng(seed.max(1))
+    }
+
+    /// Uniform in [0, 1)
+    pub(crate) fn uniform(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Normally distributed (Box-Muller)
+    pub(crate) fn normal(&mut self, mean: f64, std_dev: f64) -> f64 {
+        let u1 = self.uniform().max(f64::MIN_POSITIVE);
+        let u2 = self.uniform();
+        mean + std_dev * (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+    }
+}
+
+/// Carries snapshots of a trajectory from the remote to a `Buffer`,
+/// subject to a `NetworkProfile`. Seeded, so runs are reproducible.
+///
+/// Simulated time is measured from `start`, so that a run is timed by its
+/// simulated arrivals and frames rather than the wall clock.
+pub(crate) struct Pipeline<T> {
+    rng: Rng,
+    start: Instant,
+
+    /// (arrival time, snapshot)
+    in_flight: Vec<(f64, T)>,
+    next_send: f64,
+}
+
+impl<T: Snapshot> Pipeline<T> {
+    pub(crate) fn new(profile: &NetworkProfile) -> Self {
+        Pipeline {
+            rng: Rng::new(profile.seed),
+            start: Instant::now(),
+            in_flight: Vec::new(),
+            next_send: 0.0,
+        }
+    }
+
+    /// The local instant `now` seconds into the simulation
+    pub(crate) fn instant(&self, now: f64) -> Instant {
+        self.start + Duration::from_secs_f64(now)
+    }
+
+    /// Send any snapshots due by `now`, and insert any which have
+    /// arrived by `now` into `buf`, as if received when they arrived
+    pub(crate) fn tick(
+        &mut self,
+        now: f64,
+        truth: impl Fn(f64) -> T,
+        buf: &mut Buffer<T>,
+        settings: &Settings,
+        profile: &NetworkProfile,
+    ) {
+        // Remote sends snapshots
+        while self.next_send <= now {
+            if self.rng.uniform() >= profile.drop_rate {
+                let latency = self.rng.normal(profile.latency, profile.jitter).max(0.0);
+                self.in_flight
+                    .push((self.next_send + latency, truth(self.next_send)));
+            }
+            self.next_send += settings.period;
+        }
+
+        // Local receives snapshots, in order of arrival
+        self.in_flight.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let arrived = self
+            .in_flight
+            .iter()
+            .take_while(|(at, _)| *at <= now)
+            .count();
+        for (at, snapshot) in self.in_flight.drain(..arrived) {
+            buf.insert_snapshot_received_at(snapshot, self.start + Duration::from_secs_f64(at));
+        }
+    }
+}
+
+/// Feed the analytic trajectory `truth` (remote time -> snapshot) through
+/// a simulated network into a `Buffer` and `Playback`, measuring how far the
+/// interpolated output strays from the ground truth at the playback time.
+pub fn evaluate<T: Snapshot + Distance>(
+    truth: impl Fn(f64) -> T,
+    settings: &'static Settings,
+    network_profile: &NetworkProfile,
+) -> EvalReport {
+    let profile = network_profile;
+    let mut pipeline = Pipeline::new(profile);
+
+    let mut buf = Buffer::new(settings);
+    let mut play = Playback::new(&buf);
+
+    let mut report = EvalReport::default();
+    let mut sum_sq_error = 0.0;
+    let mut extrapolated = 0;
+
+    let mut now = 0.0;
+    while now < profile.duration {
+        pipeline.tick(now, &truth, &mut buf, settings, profile);
+
+        let output = play.step_instant(pipeline.instant(now), &buf);
+
+        // The playback time at which `step_instant` sampled
+        let sample_time = play.time_epoch() + play.playback_time + settings.lookahead() as f64;
+        let extrapolating = buf
+            .latest()
+            .is_none_or(|latest| sample_time > latest.remote_time());
+
+        if now >= profile.warmup
+            && let Some(output) = output
+        {
+            let error = output.distance(&truth(sample_time));
+            sum_sq_error += error * error;
+            report.max_error = report.max_error.max(error);
+            if extrapolating {
+                extrapolated += 1;
+            }
+            report.steps += 1;
+        }
+
+        now += profile.frame_time;
+    }
+
+    if report.steps > 0 {
+        report.rms_error = (sum_sq_error / report.steps as f64).sqrt();
+        report.extrapolation_fraction = extrapolated as f64 / report.steps as f64;
+    }
+
+    report
+}
+
+/// The state of a `SimHarness` run after a single local step
+#[derive(Clone, Debug)]
+pub struct FrameRecord<T> {
+    /// (seconds)
+    pub local_time: f64,
+
+    /// The remote time interpolated at (seconds)
+    pub playback_time: f64,
+
+    /// The output of the step
+    pub output: Option<T>,
+
+    /// The trajectory at the playback time
+    pub truth: T,
+
+    /// The playback's debug EMAs after the step
+    pub extrapolating: f64,
+    pub clamping: f64,
+    pub scaling: f64,
+
+    /// How far the playback time is behind its target, see
+    /// `Playback::instantaneous_error` (seconds)
+    pub drift: f64,
+}
+
+impl<T: Distance> FrameRecord<T> {
+    /// The error between the output and the ground truth
+    pub fn error(&self) -> Option<f64> {
+        self.output
+            .as_ref()
+            .map(|output| output.distance(&self.truth))
+    }
+}
+
+/// Builds a simulated network between a trajectory and a `Buffer` and
+/// `Playback`, recording every local step. For asserting statistical
+/// properties of playback under realistic conditions.
+pub struct SimHarness<T> {
+    settings: &'static Settings,
+    profile: NetworkProfile,
+    trajectory: Option<Box<dyn Fn(f64) -> T>>,
+}
+
+impl<T: Snapshot> SimHarness<T> {
+    pub fn new(settings: &'static Settings) -> Self {
+        SimHarness {
+            settings,
+            profile: NetworkProfile::default(),
+            trajectory: None,
+        }
+    }
+
+    pub fn with_profile(mut self, profile: NetworkProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// The ground truth, remote time (seconds) -> snapshot
+    pub fn with_trajectory(mut self, trajectory: impl Fn(f64) -> T + 'static) -> Self {
+        self.trajectory = Some(Box::new(trajectory));
+        self
+    }
+
+    /// Simulate `seconds` of local time, stepping once per the profile's
+    /// frame time at simulated instants, so a run is independent of the
+    /// wall clock. Ignores the profile's duration and warmup.
+    ///
+    /// Panics if no trajectory was given.
+    pub fn run_for(&self, seconds: f64) -> Vec<FrameRecord<T>> {
+        let truth = self
+            .trajectory
+            .as_ref()
+            .expect("SimHarness requires a trajectory");
+        let settings = self.settings;
+        let profile = &self.profile;
+
+        let mut pipeline = Pipeline::new(profile);
+        let mut buf = Buffer::new(settings);
+        let mut play = Playback::new(&buf);
+        let mut records = Vec::new();
+
+        let mut now = 0.0;
+        while now < seconds {
+            pipeline.tick(now, truth, &mut buf, settings, profile);
+
+            let at = pipeline.instant(now);
+            let output = play.step_instant(at, &buf);
+            let playback_time =
+                play.time_epoch() + play.playback_time + settings.lookahead() as f64;
+            records.push(FrameRecord {
+                local_time: now,
+                playback_time,
+                output,
+                truth: truth(playback_time),
+                extrapolating: play.db_extrapolating_ema.value.unwrap_or(0.0),
+                clamping: play.db_clamping_ema.value.unwrap_or(0.0),
+                scaling: play.db_scaling_ema.value.unwrap_or(0.0),
+                drift: play.target_playback_time_at(Some(at), &buf) - play.playback_time,
+            });
+
+            now += profile.frame_time;
+        }
+
+        records
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        SNAPSHOT_SETTINGS_DEFAULT, Settings, Snapshot, lerp,
+        testing::{Distance, NetworkProfile, SimHarness, evaluate},
+    };
+
+    #[derive(Clone, Debug)]
+    struct Linear {
+        time: f64,
+        x: f64,
+    }
+
+    impl Snapshot for Linear {
+        fn interpolate(t: f64, from: &Self, to: &Self) -> Self {
+            Linear {
+                time: lerp(from.time, to.time, t),
+                x: lerp(from.x, to.x, t),
+            }
+        }
+
+        fn remote_time(&self) -> f64 {
+            self.time
+        }
+    }
+
+    impl Distance for Linear {
+        fn distance(&self, other: &Self) -> f64 {
+            (self.x - other.x).abs()
+        }
+    }
+
+    fn leak(settings: Settings) -> &'static Settings {
+        Box::leak(Box::new(settings))
+    }
+
+    #[test]
+    fn test_evaluate_perfect_network() {
+        let report = evaluate(
+            |time| Linear {
+                time,
+                x: time * 3.0,
+            },
+            &SNAPSHOT_SETTINGS_DEFAULT,
+            &NetworkProfile::perfect(),
+        );
+
+        assert!(report.steps > 0);
+        assert!(report.rms_error < 1e-6, "{report:?}");
+        assert!(report.max_error < 1e-6, "{report:?}");
+    }
+
+    #[test]
+    fn test_evaluate_lookahead() {
+        let settings = leak(Settings {
+            lookahead_periods: 0.5,
+            ..Settings::default()
+        });
+        let report = evaluate(
+            |time| Linear {
+                time,
+                x: time * 3.0,
+            },
+            settings,
+            &NetworkProfile::perfect(),
+        );
+
+        // Measured against the truth at the time sampled, lookahead included
+        assert!(report.rms_error < 1e-6, "{report:?}");
+    }
+
+    #[test]
+    fn test_evaluate_simulated_network() {
+        let truth = |time: f64| Linear {
+            time,
+            x: time.sin(),
+        };
+        let good = evaluate(truth, &SNAPSHOT_SETTINGS_DEFAULT, &NetworkProfile::good());
+        let poor = evaluate(truth, &SNAPSHOT_SETTINGS_DEFAULT, &NetworkProfile::poor());
+
+        // Simulated arrivals, not the wall clock, time the run
+        assert!(poor.extrapolation_fraction > good.extrapolation_fraction);
+        assert!(poor.max_error > good.max_error, "{good:?} {poor:?}");
+    }
+
+    #[test]
+    fn test_sim_harness_good_network() {
+        let records = SimHarness::new(&SNAPSHOT_SETTINGS_DEFAULT)
+            .with_profile(NetworkProfile::good())
+            .with_trajectory(|time| Linear {
+                time,
+                x: time * 3.0,
+            })
+            .run_for(10.0);
+
+        let errors = records
+            .iter()
+            .filter(|record| record.local_time >= 2.0)
+            .filter_map(|record| record.error())
+            .collect::<Vec<_>>();
+        let mean_error = errors.iter().sum::<f64>() / errors.len() as f64;
+
+        assert!(!errors.is_empty());
+        assert!(mean_error < 0.01, "{mean_error}");
+    }
+
+    #[test]
+    fn test_sim_harness_simulated_clock() {
+        let settings = &SNAPSHOT_SETTINGS_DEFAULT;
+        let profile = NetworkProfile::perfect();
+        let frame_time = profile.frame_time;
+        let records = SimHarness::new(settings)
+            .with_profile(profile)
+            .with_trajectory(|time| Linear { time, x: time })
+            .run_for(10.0);
+
+        // The remote's clock starts with the simulation, so the playback
+        // trails the simulated local time by the playback offset
+        for record in records.iter().filter(|record| record.local_time >= 2.0) {
+            let lag = record.local_time - record.playback_time;
+            assert!(
+                (lag - settings.playback_offset() as f64).abs() <= frame_time,
+                "{lag}"
+            );
+            assert!(record.drift.abs() < 1e-6, "{}", record.drift);
+        }
+    }
+}